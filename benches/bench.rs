@@ -1,10 +1,10 @@
-use std::io::{BufReader, ErrorKind, Seek};
+use std::io::{BufReader, Seek};
 
 use bencher::{benchmark_group, benchmark_main, Bencher};
 use molly::{
     reader,
     selection::{AtomSelection, FrameSelection},
-    Frame, Magic, XTCReader,
+    Error, Frame, Magic, XTCReader,
 };
 
 benchmark_main!(reading, decoding);
@@ -16,6 +16,8 @@ benchmark_group!(
     read_frames_buffered,
     read_frames_few_atoms,
     read_frames_few_atoms_buffered,
+    read_frame_single_atom_low,
+    read_frame_single_atom_high,
 );
 benchmark_group!(
     decoding,
@@ -31,7 +33,7 @@ fn read_frame(b: &mut Bencher) {
     let mut frame = Frame::default();
     b.iter(|| match reader.read_frame(&mut frame) {
         Ok(_) => {}
-        Err(err) if err.kind() == ErrorKind::UnexpectedEof => reader.home().unwrap(),
+        Err(Error::Truncated) => reader.home().unwrap(),
         Err(err) => panic!("{err}"),
     });
 }
@@ -45,7 +47,7 @@ fn read_frame_buffered(b: &mut Bencher) {
             reader.read_frame_with_selection_buffered(&mut frame, &AtomSelection::All)
         } {
             Ok(_) => {}
-            Err(err) if err.kind() == ErrorKind::UnexpectedEof => reader.home().unwrap(),
+            Err(Error::Truncated) => reader.home().unwrap(),
             Err(err) => panic!("{err}"),
         }
     });
@@ -91,6 +93,33 @@ fn read_frames_few_atoms_buffered(b: &mut Bencher) {
     });
 }
 
+/// Compare the cost of a single-atom selection at the start against one at the end of a frame.
+///
+/// The gap between the two shows the unavoidable sequential decoding cost of a high-`start`
+/// selection; the low-index one additionally benefits from `reading_limit` cutting the decode
+/// short.
+fn read_frame_single_atom(b: &mut Bencher, pick: fn(u32) -> u32) {
+    let mut reader = XTCReader::open(PATH).unwrap();
+    let mut frame = Frame::default();
+    reader.read_frame(&mut frame).unwrap();
+    let idx = pick(frame.natoms() as u32);
+    let selection = AtomSelection::range(Some(idx), idx + 1, None);
+    reader.home().unwrap();
+    b.iter(|| match reader.read_frame_with_selection(&mut frame, &selection) {
+        Ok(_) => {}
+        Err(Error::Truncated) => reader.home().unwrap(),
+        Err(err) => panic!("{err}"),
+    });
+}
+
+fn read_frame_single_atom_low(b: &mut Bencher) {
+    read_frame_single_atom(b, |_| 0)
+}
+
+fn read_frame_single_atom_high(b: &mut Bencher) {
+    read_frame_single_atom(b, |natoms| natoms - 1)
+}
+
 fn read_compressed_positions(b: &mut Bencher) {
     let magic = Magic::Xtc1995;
     let natoms = 125;
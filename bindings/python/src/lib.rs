@@ -74,7 +74,7 @@ impl FromPyObject<'_> for AtomSelection {
 
         if let Ok(list) = ob.downcast::<PyList>() {
             if let Ok(bools) = list.extract::<Vec<bool>>() {
-                return Ok(AtomSelection(selection::AtomSelection::Mask(bools)));
+                return Ok(AtomSelection(selection::AtomSelection::Mask(bools.into())));
             }
             if let Ok(indices) = list.extract::<Vec<u32>>() {
                 return Ok(AtomSelection(selection::AtomSelection::from_index_list(
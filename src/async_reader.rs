@@ -0,0 +1,187 @@
+//! An asynchronous xtc reader.
+//!
+//! A service that streams trajectories from object storage wants to overlap the wait for the
+//! next network read with other work, which the blocking [`XTCReader`] cannot offer.
+//! [`AsyncXTCReader`] fetches the header and the compressed coordinate block of a frame through
+//! [`tokio::io`], and then decodes the fetched bytes through the very same code path as the sync
+//! reader, so the two can never disagree. Decoding is CPU-bound and runs on the current task;
+//! for very large frames, the caller can move a read into
+//! [`spawn_blocking`](tokio::task::spawn_blocking).
+//!
+//! This module is only available behind the `async` feature.
+
+use std::io::{self, Cursor};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+
+use crate::reader::NBYTES_POSITIONS_PRELUDE;
+use crate::selection::AtomSelection;
+use crate::{padding, Error, Frame, Header, Magic, XTCReader};
+
+/// An asynchronous reader over the frames of an xtc file.
+///
+/// The reader is sequential: each [`read_frame`](AsyncXTCReader::read_frame) call fetches and
+/// decodes the next frame. Selection handling matches [`XTCReader`].
+pub struct AsyncXTCReader<R> {
+    pub file: R,
+    pub step: usize,
+    /// The default [`AtomSelection`] applied by [`AsyncXTCReader::read_frame`].
+    ///
+    /// Contrary to the sync reader, the selection is validated against the trajectory when a
+    /// frame is read, since peeking at a header up front would require a fetch of its own.
+    atom_selection: AtomSelection,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> AsyncXTCReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            file: reader,
+            step: 0,
+            atom_selection: AtomSelection::All,
+        }
+    }
+
+    /// Set the default [`AtomSelection`] applied by [`AsyncXTCReader::read_frame`].
+    ///
+    /// The selection is validated against the number of atoms of each frame as it is read.
+    pub fn set_atom_selection(&mut self, atom_selection: AtomSelection) {
+        self.atom_selection = atom_selection;
+    }
+
+    /// Returns the default [`AtomSelection`] applied by [`AsyncXTCReader::read_frame`].
+    pub fn atom_selection(&self) -> &AtomSelection {
+        &self.atom_selection
+    }
+
+    /// Reset the reader to its initial position.
+    ///
+    /// Go back to the first frame.
+    pub async fn home(&mut self) -> io::Result<()> {
+        self.file.seek(io::SeekFrom::Start(0)).await?;
+        self.step = 0;
+        Ok(())
+    }
+
+    /// Read the next frame, if any remain.
+    ///
+    /// The atoms are read according to the selection set through
+    /// [`AsyncXTCReader::set_atom_selection`]. Returns [`None`] once the end of the trajectory
+    /// is reached.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors.
+    pub async fn read_frame(&mut self) -> io::Result<Option<Frame>> {
+        let atom_selection = std::mem::take(&mut self.atom_selection);
+        let result = self.read_frame_with_selection(&atom_selection).await;
+        self.atom_selection = atom_selection;
+        result
+    }
+
+    /// Read the next frame according to an [`AtomSelection`], if any remain.
+    ///
+    /// See [`AsyncXTCReader::read_frame`].
+    pub async fn read_frame_with_selection(
+        &mut self,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<Option<Frame>> {
+        // Fetch the header, and learn how many bytes of positions follow it.
+        let mut bytes = vec![0; Header::SIZE];
+        match self.file.read_exact(&mut bytes).await {
+            Ok(_) => {}
+            // We have found the end of the file. No more frames, we're done.
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err),
+        }
+        let found = i32::from_be_bytes(bytes[..4].try_into().unwrap());
+        let magic = Magic::try_from(found).map_err(|_| Error::BadMagic { found })?;
+        let natoms = u32::from_be_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        atom_selection.validate(natoms).map_err(io::Error::other)?;
+
+        // Fetch the positions: raw floats for a small system, otherwise the precision, the
+        // prelude, and the compressed block including its padding.
+        if natoms <= 9 {
+            let start = bytes.len();
+            bytes.resize(start + natoms * 3 * 4, 0);
+            self.file.read_exact(&mut bytes[start..]).await?;
+        } else {
+            let nbytes_field = match magic {
+                Magic::Xtc1995 => 4,
+                Magic::Xtc2023 => 8,
+            };
+            let start = bytes.len();
+            bytes.resize(start + 4 + NBYTES_POSITIONS_PRELUDE + nbytes_field, 0);
+            self.file.read_exact(&mut bytes[start..]).await?;
+            let nbytes = match magic {
+                Magic::Xtc1995 => {
+                    u32::from_be_bytes(bytes[bytes.len() - 4..].try_into().unwrap()) as usize
+                }
+                Magic::Xtc2023 => {
+                    u64::from_be_bytes(bytes[bytes.len() - 8..].try_into().unwrap()) as usize
+                }
+            };
+            let start = bytes.len();
+            bytes.resize(start + nbytes + padding(nbytes), 0);
+            self.file.read_exact(&mut bytes[start..]).await?;
+        }
+
+        // Decode the fetched bytes through the sync code path.
+        let mut decoder = XTCReader::new(Cursor::new(bytes));
+        decoder.step = self.step;
+        let mut frame = Frame::default();
+        decoder.read_frame_with_selection(&mut frame, atom_selection)?;
+        self.step += 1;
+
+        Ok(Some(frame))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writer::XTCWriter;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn matches_sync_reading() -> io::Result<()> {
+        // Write a trajectory into memory, including a small-system frame for the raw float path.
+        let mut writer = XTCWriter::new(Cursor::new(Vec::new()));
+        for step in 0..5 {
+            writer.write_frame(&Frame {
+                step,
+                time: step as f32,
+                precision: 1000.0,
+                positions: (0..3 * 40).map(|v| (v + step as usize) as f32 * 0.01).collect(),
+                ..Frame::default()
+            })?;
+        }
+        writer.write_frame(&Frame {
+            step: 5,
+            positions: vec![0.5; 3 * 4],
+            ..Frame::default()
+        })?;
+        let bytes = writer.file.into_inner();
+
+        let mut sync_reader = XTCReader::new(Cursor::new(bytes.clone()));
+        let expected = sync_reader.read_all_frames()?;
+        assert_eq!(expected.len(), 6);
+
+        let mut reader = AsyncXTCReader::new(Cursor::new(bytes));
+        let mut frames = Vec::new();
+        while let Some(frame) = reader.read_frame().await? {
+            frames.push(frame);
+        }
+        assert_eq!(frames.as_slice(), &expected[..]);
+        assert_eq!(reader.step, 6);
+
+        // Selections apply just as they do on the sync path.
+        reader.home().await?;
+        reader.set_atom_selection(AtomSelection::Until(10));
+        let frame = reader.read_frame().await?.unwrap();
+        assert_eq!(frame.positions, expected[0].positions[..10 * 3]);
+        // An oversized selection is rejected when the frame is read.
+        reader.set_atom_selection(AtomSelection::Until(100));
+        assert!(reader.read_frame().await.is_err());
+
+        Ok(())
+    }
+}
@@ -30,6 +30,9 @@ pub trait Buffered<'s, 'r, R>: Sized {
     /// Returns the byte position of the reader.
     fn tell(&self) -> usize;
 
+    /// Returns the total size of the compressed block in bytes, including its padding.
+    fn size(&self) -> usize;
+
     /// Finish will eat your reader, leaving it at the start of the next frame, and then drops it.
     ///
     /// For an implementation that relies on [`std::io::Seek`] ([`Buffer`] in our case), this
@@ -38,7 +41,7 @@ pub trait Buffered<'s, 'r, R>: Sized {
 }
 
 /// A specialized buffered reader for the compressed datastream.
-pub struct Buffer<'s, 'r> {
+pub struct Buffer<'s, 'r, R = File> {
     /// Internal scratch buffer to read into.
     ///
     /// # Warning
@@ -51,13 +54,13 @@ pub struct Buffer<'s, 'r> {
     front: usize,
     /// Points to the last-most byte that has been read.
     head: usize,
-    reader: &'r mut File,
+    reader: &'r mut R,
     // TODO(buffered): Add some notion of a 'rich' heuristic. For instance, if we know there are
     // 1000 atoms, and we only want to read up until the 500th atom, we can pretty safely assume
     // that we can just read (500/1000) * 1.1 * nbytes = 0.55 * nbytes and be fine.
 }
 
-impl Buffer<'_, '_> {
+impl<R: Read> Buffer<'_, '_, R> {
     const BLOCK_SIZE: usize = 0x20000;
     const MIN_BUFFERED_SIZE: usize = 2 * Self::BLOCK_SIZE;
 
@@ -92,8 +95,8 @@ impl Buffer<'_, '_> {
     }
 }
 
-impl<'s, 'r> Buffered<'s, 'r, File> for Buffer<'s, 'r> {
-    fn new(scratch: &'s mut Vec<u8>, reader: &'r mut File, magic: Magic) -> io::Result<Self> {
+impl<'s, 'r, R: Read + Seek> Buffered<'s, 'r, R> for Buffer<'s, 'r, R> {
+    fn new(scratch: &'s mut Vec<u8>, reader: &'r mut R, magic: Magic) -> io::Result<Self> {
         let count = read_nbytes(reader, magic)?;
 
         // Fill the scratch buffer with a cautionary value.
@@ -139,6 +142,10 @@ impl<'s, 'r> Buffered<'s, 'r, File> for Buffer<'s, 'r> {
         self.head
     }
 
+    fn size(&self) -> usize {
+        self.scratch.len()
+    }
+
     fn finish(self) -> io::Result<()> {
         self.reader.seek(SeekFrom::Current(self.left() as i64))?;
         Ok(())
@@ -176,6 +183,10 @@ impl<'s, 'r, R: Read> Buffered<'s, 'r, R> for UnBuffered<'s> {
         self.head
     }
 
+    fn size(&self) -> usize {
+        self.scratch.len()
+    }
+
     fn finish(self) -> io::Result<()> {
         Ok(()) // Nothing to do, since we already read everything.
     }
@@ -0,0 +1,236 @@
+//! Chaining trajectory parts into one logical trajectory.
+//!
+//! Long simulations are commonly run in restartable chunks, leaving a series of files like
+//! `traj.part0001.xtc`, `traj.part0002.xtc`, and so on. [`XTCChain`] wraps such an ordered list
+//! of readers and presents them as a single continuous stream of frames. Since a restart usually
+//! rewrites the frame it restarted from, the frame at a part boundary often appears twice; the
+//! chain can deduplicate those on the fly.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::selection::{AtomSelection, FrameSelection};
+use crate::{Frame, XTCReader};
+
+/// A chain of xtc readers, presented as one continuous trajectory.
+///
+/// Frames are read from the wrapped readers in order, moving on to the next reader once one is
+/// exhausted. With deduplication enabled, a frame at a part boundary whose step repeats the step
+/// of the previously read frame is dropped, which undoes the overlap that simulation restarts
+/// leave behind.
+///
+/// A [`FrameSelection`] passed to [`XTCChain::frames`] addresses the concatenated virtual
+/// trajectory: frame index 0 is the first frame of the first part, and a dropped duplicate does
+/// not occupy an index.
+pub struct XTCChain<R> {
+    readers: Vec<XTCReader<R>>,
+    /// Whether to drop a boundary frame that repeats the step of the frame before it.
+    dedup: bool,
+    /// The reader that the next frame will be read from.
+    current: usize,
+    /// Whether the next frame is the first of a new part.
+    at_boundary: bool,
+    /// The step of the last frame that was read.
+    last_step: Option<u32>,
+    /// The index within the virtual trajectory of the next frame.
+    frame_idx: usize,
+}
+
+impl XTCChain<File> {
+    /// Open the trajectory parts at `paths`, in the provided order.
+    pub fn open<P: AsRef<Path>>(paths: &[P], dedup: bool) -> io::Result<Self> {
+        let readers = paths
+            .iter()
+            .map(XTCReader::open)
+            .collect::<io::Result<_>>()?;
+        Ok(Self::new(readers, dedup))
+    }
+}
+
+impl<R: Read> XTCChain<R> {
+    /// Chain the provided readers, in the provided order.
+    pub fn new(readers: Vec<XTCReader<R>>, dedup: bool) -> Self {
+        Self {
+            readers,
+            dedup,
+            current: 0,
+            at_boundary: false,
+            last_step: None,
+            frame_idx: 0,
+        }
+    }
+
+    /// Returns the index within the virtual trajectory of the next frame.
+    pub fn frame_idx(&self) -> usize {
+        self.frame_idx
+    }
+
+    /// Read the next frame of the virtual trajectory into `frame`, reusing its allocations.
+    ///
+    /// Returns `false` once the last reader in the chain is exhausted.
+    pub fn read_frame_into(&mut self, frame: &mut Frame) -> io::Result<bool> {
+        self.read_frame_into_with_selection(frame, &AtomSelection::All)
+    }
+
+    /// Read the next frame of the virtual trajectory into `frame` according to an
+    /// [`AtomSelection`], reusing its allocations.
+    ///
+    /// See [`XTCChain::read_frame_into`].
+    pub fn read_frame_into_with_selection(
+        &mut self,
+        frame: &mut Frame,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<bool> {
+        loop {
+            let Some(reader) = self.readers.get_mut(self.current) else {
+                return Ok(false);
+            };
+            if !reader.read_frame_into_with_selection(frame, atom_selection)? {
+                // This part is exhausted; move on to the next.
+                self.current += 1;
+                self.at_boundary = true;
+                continue;
+            }
+
+            let duplicate =
+                self.dedup && self.at_boundary && self.last_step == Some(frame.step);
+            self.at_boundary = false;
+            self.last_step = Some(frame.step);
+            if duplicate {
+                // A restart rewrote this frame at the end of the previous part; drop it.
+                continue;
+            }
+
+            self.frame_idx += 1;
+            return Ok(true);
+        }
+    }
+
+    /// Returns an iterator over the frames of the virtual trajectory selected by
+    /// `frame_selection`.
+    ///
+    /// The selection indices address the virtual trajectory, so a selection carries over part
+    /// boundaries transparently.
+    pub fn frames<'r>(
+        &'r mut self,
+        frame_selection: &'r FrameSelection,
+        atom_selection: &'r AtomSelection,
+    ) -> ChainFrames<'r, R> {
+        ChainFrames {
+            chain: self,
+            frame_selection,
+            atom_selection,
+            done: false,
+        }
+    }
+}
+
+/// An iterator over the selected frames of a chain, created by [`XTCChain::frames`].
+///
+/// Yields `io::Result<Frame>`, such that decode errors are propagated to the caller rather than
+/// silently terminating the iteration.
+pub struct ChainFrames<'r, R> {
+    chain: &'r mut XTCChain<R>,
+    frame_selection: &'r FrameSelection,
+    atom_selection: &'r AtomSelection,
+    done: bool,
+}
+
+impl<R: Read> Iterator for ChainFrames<'_, R> {
+    type Item = io::Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let included = match self.frame_selection.is_included(self.chain.frame_idx) {
+                Some(included) => included,
+                // The selection holds no frames beyond this point.
+                None => break,
+            };
+            let mut frame = Frame::default();
+            match self
+                .chain
+                .read_frame_into_with_selection(&mut frame, self.atom_selection)
+            {
+                Ok(true) if included => return Some(Ok(frame)),
+                Ok(true) => {}
+                Ok(false) => self.done = true,
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::selection::Range;
+    use crate::writer::XTCWriter;
+
+    /// Write a trajectory part holding the provided steps.
+    fn write_part(steps: &[u32]) -> io::Result<Vec<u8>> {
+        let mut writer = XTCWriter::new(io::Cursor::new(Vec::new()));
+        for &step in steps {
+            writer.write_frame(&Frame {
+                step,
+                time: step as f32,
+                precision: 1000.0,
+                positions: (0..3 * 20).map(|v| v as f32 * 0.01).collect(),
+                ..Frame::default()
+            })?;
+        }
+        Ok(writer.file.into_inner())
+    }
+
+    fn chain(parts: &[Vec<u8>], dedup: bool) -> XTCChain<&[u8]> {
+        XTCChain::new(
+            parts
+                .iter()
+                .map(|part| XTCReader::new(part.as_slice()))
+                .collect(),
+            dedup,
+        )
+    }
+
+    #[test]
+    fn boundary_deduplication() -> io::Result<()> {
+        // Two parts sharing the frame at step 20, as a restart leaves behind.
+        let parts = [write_part(&[0, 10, 20])?, write_part(&[20, 30, 40])?];
+
+        let read_steps = |dedup: bool| -> io::Result<Vec<u32>> {
+            let mut chain = chain(&parts, dedup);
+            let mut steps = Vec::new();
+            let mut frame = Frame::default();
+            while chain.read_frame_into(&mut frame)? {
+                steps.push(frame.step);
+            }
+            Ok(steps)
+        };
+
+        assert_eq!(read_steps(true)?, [0, 10, 20, 30, 40]);
+        assert_eq!(read_steps(false)?, [0, 10, 20, 20, 30, 40]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn selection_over_virtual_indices() -> io::Result<()> {
+        let parts = [write_part(&[0, 10, 20])?, write_part(&[20, 30, 40])?];
+
+        // With dedup, the virtual trajectory holds steps [0, 10, 20, 30, 40]; a range over it
+        // carries over the part boundary transparently.
+        let mut chain = chain(&parts, true);
+        let selection = FrameSelection::Range(Range::new(Some(1), Some(4), None));
+        let steps: Vec<u32> = chain
+            .frames(&selection, &AtomSelection::All)
+            .map(|frame| Ok(frame?.step))
+            .collect::<io::Result<_>>()?;
+        assert_eq!(steps, [10, 20, 30]);
+
+        Ok(())
+    }
+}
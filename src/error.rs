@@ -0,0 +1,127 @@
+use std::fmt;
+use std::io;
+
+use crate::Magic;
+
+/// A specialized result type for reading trajectories.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The ways reading a trajectory can go wrong.
+///
+/// Where a bare [`io::Error`] only offers a stringly message, these variants let callers
+/// distinguish a truncated file from a bad magic number from a corrupt header, and build retry or
+/// skip logic on typed matches.
+///
+/// `Error` converts from and into [`io::Error`] (a truncation maps onto
+/// [`io::ErrorKind::UnexpectedEof`] in both directions), so it composes with `?` in functions
+/// returning either result type.
+#[derive(Debug)]
+pub enum Error {
+    /// The file ended partway through a frame, or partway through its header.
+    Truncated,
+    /// A frame header started with an invalid magic number.
+    BadMagic {
+        /// The value found where a magic number was expected.
+        found: i32,
+    },
+    /// A frame header holds a step value that does not fit.
+    StepOutOfRange {
+        /// The step value found in the header.
+        found: i32,
+    },
+    /// The natoms value of a frame disagrees with what the rest of the trajectory declares.
+    ///
+    /// Returned when the repeated natoms value within one frame header does not match the first,
+    /// which indicates a corrupt frame, or—with
+    /// [`XTCReader::expect_constant_natoms`](crate::XTCReader::expect_constant_natoms)
+    /// enabled—when a frame declares a different number of atoms than the frames before it.
+    UnexpectedNatoms {
+        expected: usize,
+        found: usize,
+        /// The index of the offending frame, where known.
+        frame: Option<usize>,
+    },
+    /// A decoded frame holds a coordinate that is NaN or infinite.
+    ///
+    /// Only returned when the check is enabled through
+    /// [`XTCReader::check_finite`](crate::XTCReader::check_finite).
+    NonFiniteCoords {
+        /// The index of the frame that holds the offending coordinate.
+        frame: usize,
+        /// The index of the first atom with a non-finite coordinate, within the selection the
+        /// frame was read with.
+        atom: usize,
+    },
+    /// An underlying io error.
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "the file ended partway through a frame"),
+            Self::BadMagic { found } => write!(
+                f,
+                "found invalid magic number '{found}' ({found:#0x}), {} and {} are supported",
+                Magic::XTC_1995,
+                Magic::XTC_2023
+            ),
+            Self::StepOutOfRange { found } => {
+                write!(f, "the step value in the header ({found}) is out of range")
+            }
+            Self::UnexpectedNatoms {
+                expected,
+                found,
+                frame: None,
+            } => write!(
+                f,
+                "the repeated natoms value in the header ({found}) does not match the first \
+                ({expected}), the frame is likely corrupt"
+            ),
+            Self::UnexpectedNatoms {
+                expected,
+                found,
+                frame: Some(frame),
+            } => write!(
+                f,
+                "frame {frame} declares {found} atoms, where the frames before it declare \
+                {expected}"
+            ),
+            Self::NonFiniteCoords { frame, atom } => write!(
+                f,
+                "atom {atom} in frame {frame} decoded to a coordinate that is not finite"
+            ),
+            Self::Io(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => Self::Truncated,
+            _ => Self::Io(err),
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Truncated => {
+                io::Error::new(io::ErrorKind::UnexpectedEof, err.to_string())
+            }
+            Error::Io(err) => err,
+            other => io::Error::other(other),
+        }
+    }
+}
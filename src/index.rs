@@ -0,0 +1,532 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use crate::reader::{read_f32, read_nbytes, read_u32, read_u64};
+use crate::selection::{AtomSelection, FrameSelection};
+use crate::{padding, Frame, Header, XTCReader};
+
+/// The position and header metadata of a single frame within a trajectory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IndexEntry {
+    /// The byte offset of the frame header from the start of the trajectory.
+    pub offset: u64,
+    pub step: u32,
+    /// Time in picoseconds.
+    pub time: f32,
+    /// The number of atoms the frame declares.
+    pub natoms: u32,
+}
+
+/// An index over the frames of an xtc file, for O(1) random access.
+///
+/// An `XTCIndex` is produced by [`XTCReader::build_index`] and can be persisted to a sidecar file
+/// through [`XTCIndex::write_to`] and read back with [`XTCIndex::read_from`], so that it can be
+/// cached between runs. The size and modification time of the trajectory are stored along with the
+/// offsets, such that an index that no longer matches its trajectory can be detected and rejected
+/// (see [`XTCIndex::load`] and [`XTCIndex::is_stale`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct XTCIndex {
+    /// The indexed frames, in file order.
+    pub frames: Vec<IndexEntry>,
+    /// The size in bytes of the trajectory this index was built from.
+    source_size: u64,
+    /// The modification time of the trajectory this index was built from, in whole seconds since
+    /// the unix epoch.
+    source_mtime: u64,
+}
+
+/// The magic bytes at the start of a serialized [`XTCIndex`].
+const INDEX_MAGIC: [u8; 8] = *b"MOLLYIDX";
+/// The version of the serialized [`XTCIndex`] layout.
+const INDEX_VERSION: u32 = 1;
+
+impl XTCIndex {
+    /// Returns the number of indexed frames.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Serialize this [`XTCIndex`] into a writer.
+    ///
+    /// All values are stored big-endian, consistent with the xdr convention of the xtc format
+    /// itself.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&INDEX_MAGIC)?;
+        writer.write_all(&INDEX_VERSION.to_be_bytes())?;
+        writer.write_all(&self.source_size.to_be_bytes())?;
+        writer.write_all(&self.source_mtime.to_be_bytes())?;
+        writer.write_all(&(self.frames.len() as u64).to_be_bytes())?;
+        for entry in &self.frames {
+            writer.write_all(&entry.offset.to_be_bytes())?;
+            writer.write_all(&entry.step.to_be_bytes())?;
+            writer.write_all(&entry.time.to_be_bytes())?;
+            writer.write_all(&entry.natoms.to_be_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Deserialize an [`XTCIndex`] from a reader.
+    ///
+    /// Note that this function does not check whether the index is stale with respect to the
+    /// trajectory it was built from. Use [`XTCIndex::load`] or [`XTCIndex::is_stale`] for that.
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if magic != INDEX_MAGIC {
+            return Err(io::Error::other("not a molly xtc index file"));
+        }
+        let version = read_u32(reader)?;
+        if version != INDEX_VERSION {
+            return Err(io::Error::other(format!(
+                "unsupported xtc index version {version}, expected {INDEX_VERSION}"
+            )));
+        }
+        let source_size = read_u64(reader)?;
+        let source_mtime = read_u64(reader)?;
+        let nframes = read_u64(reader)? as usize;
+        let mut frames = Vec::with_capacity(nframes);
+        for _ in 0..nframes {
+            frames.push(IndexEntry {
+                offset: read_u64(reader)?,
+                step: read_u32(reader)?,
+                time: read_f32(reader)?,
+                natoms: read_u32(reader)?,
+            });
+        }
+        Ok(Self {
+            frames,
+            source_size,
+            source_mtime,
+        })
+    }
+
+    /// Read an [`XTCIndex`] from a sidecar file and verify it against its trajectory.
+    ///
+    /// # Errors
+    ///
+    /// Next to passing through any reader errors, this function will return an error if the index
+    /// is stale, i.e., if the size or modification time of the trajectory no longer match the
+    /// values that were recorded when the index was built.
+    pub fn load<P: AsRef<Path>>(index_path: P, trajectory_path: P) -> io::Result<Self> {
+        let index = Self::read_from(&mut File::open(index_path)?)?;
+        if index.is_stale(trajectory_path)? {
+            return Err(io::Error::other(
+                "the xtc index is stale: the trajectory was modified after the index was built",
+            ));
+        }
+        Ok(index)
+    }
+
+    /// Returns whether this [`XTCIndex`] no longer matches the trajectory at the provided path.
+    pub fn is_stale<P: AsRef<Path>>(&self, trajectory_path: P) -> io::Result<bool> {
+        let metadata = std::fs::metadata(trajectory_path)?;
+        let (size, mtime) = size_and_mtime(&metadata)?;
+        Ok(size != self.source_size || mtime != self.source_mtime)
+    }
+}
+
+fn size_and_mtime(metadata: &std::fs::Metadata) -> io::Result<(u64, u64)> {
+    let mtime = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map_err(io::Error::other)?
+        .as_secs();
+    Ok((metadata.len(), mtime))
+}
+
+impl XTCReader<File> {
+    /// Scan the trajectory and build an [`XTCIndex`] recording the byte offset, step, and time of
+    /// each frame.
+    ///
+    /// Only the frame headers are read; the coordinate blocks are skipped over, so building an
+    /// index is much cheaper than reading the frames. The reader is returned to the position it
+    /// was at before this function was called.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors.
+    pub fn build_index(&mut self) -> io::Result<XTCIndex> {
+        let (source_size, source_mtime) = size_and_mtime(&self.file.metadata()?)?;
+
+        let file = &mut self.file;
+        // Remember where we start so we can return to it later.
+        let start_pos = file.stream_position()?;
+        file.seek(SeekFrom::Start(0))?;
+
+        let mut frames = Vec::new();
+        let mut offset = 0;
+        loop {
+            let header = match Header::read(file) {
+                Ok(header) => header,
+                Err(crate::Error::Truncated) => break,
+                Err(err) => Err(err)?,
+            };
+            frames.push(IndexEntry {
+                offset,
+                step: header.step,
+                time: header.time,
+                natoms: header.natoms as u32,
+            });
+
+            let skip = if header.natoms <= 9 {
+                // Know how many bytes are in this frame until the next header since the positions
+                // are uncompressed.
+                header.natoms as u64 * 3 * 4
+            } else {
+                // We need to read the nbytes value to get the offset until the next header.
+                file.seek(SeekFrom::Current(32))?;
+                // The size of the buffer is stored either as a 64 or 32-bit integer, depending on
+                // the magic number in the header.
+                let nbytes = read_nbytes(file, header.magic)? as u64;
+                nbytes + padding(nbytes as usize) as u64
+            };
+            offset = file.seek(SeekFrom::Current(skip as i64))?;
+        }
+
+        // Return back to where we started.
+        file.seek(SeekFrom::Start(start_pos))?;
+
+        Ok(XTCIndex {
+            frames,
+            source_size,
+            source_mtime,
+        })
+    }
+
+    /// Position the reader at the start of the frame with index `frame`, according to an
+    /// [`XTCIndex`].
+    ///
+    /// After a successful seek, the next call to [`XTCReader::read_frame`] (or friends) will read
+    /// the requested frame.
+    ///
+    /// # Errors
+    ///
+    /// Next to passing through any reader errors, this function will return an error if `frame`
+    /// lies beyond the indexed range.
+    pub fn seek_to_frame(&mut self, index: &XTCIndex, frame: usize) -> io::Result<()> {
+        let Some(entry) = index.frames.get(frame) else {
+            return Err(io::Error::other(format!(
+                "cannot seek to frame {frame}: the index holds {} frames",
+                index.len()
+            )));
+        };
+        self.file.seek(SeekFrom::Start(entry.offset))?;
+        self.step = frame;
+        Ok(())
+    }
+
+    /// Extract the positions of a single atom across the trajectory, according to an
+    /// [`XTCIndex`].
+    ///
+    /// Returns one position per frame, in trajectory order. See
+    /// [`XTCReader::atom_trajectory_with_selection`] to extract the time series over a subset of
+    /// the frames.
+    pub fn atom_trajectory(&mut self, index: &XTCIndex, atom: u32) -> io::Result<Vec<[f32; 3]>> {
+        self.atom_trajectory_with_selection(index, atom, &FrameSelection::All)
+    }
+
+    /// Extract the positions of a single atom across the frames selected by `frame_selection`,
+    /// according to an [`XTCIndex`].
+    ///
+    /// xtc compression is frame-global, so each included frame's coordinate block must still be
+    /// decompressed. The decoder is pointed at the single atom through an [`AtomSelection`]
+    /// mask, which lets it stop decoding each frame early; for atoms near the top of the frame,
+    /// this is far cheaper than a full decode.
+    ///
+    /// # Errors
+    ///
+    /// Next to passing through any reader errors, this function will return an error if `atom`
+    /// lies beyond the atoms of an included frame.
+    pub fn atom_trajectory_with_selection(
+        &mut self,
+        index: &XTCIndex,
+        atom: u32,
+        frame_selection: &FrameSelection,
+    ) -> io::Result<Vec<[f32; 3]>> {
+        let atom_selection = AtomSelection::from_index_list(&[atom]);
+        let mut positions = Vec::new();
+        let mut frame = Frame::default();
+        for (idx, entry) in index.frames.iter().enumerate() {
+            match frame_selection.is_included(idx) {
+                Some(true) => {}
+                Some(false) => continue,
+                // The selection holds no frames beyond this point.
+                None => break,
+            }
+            // The index records each frame's atom count, so an atom beyond the frame is caught
+            // before any decoding.
+            if atom >= entry.natoms {
+                return Err(io::Error::other(format!(
+                    "atom {atom} lies beyond the {} atoms of frame {idx}",
+                    entry.natoms
+                )));
+            }
+            self.file.seek(SeekFrom::Start(entry.offset))?;
+            self.read_frame_with_selection(&mut frame, &atom_selection)?;
+            positions.push(frame.positions[0..3].try_into().unwrap());
+        }
+        Ok(positions)
+    }
+
+    /// Returns an iterator over the frames of the trajectory in reverse order, according to an
+    /// [`XTCIndex`].
+    ///
+    /// Xtc frames hold no backward links, so walking a trajectory from the last frame to the
+    /// first requires the offsets recorded in the index. See
+    /// [`XTCReader::frames_rev_with_selection`] to restrict the frames and atoms that are read.
+    pub fn frames_rev<'r, 'i>(&'r mut self, index: &'i XTCIndex) -> FramesRev<'r, 'i> {
+        self.frames_rev_with_selection(index, FrameSelection::All, AtomSelection::All)
+    }
+
+    /// Returns an iterator over the selected frames of the trajectory in reverse order,
+    /// according to an [`XTCIndex`].
+    ///
+    /// The frames included in the [`FrameSelection`] are yielded in descending order of their
+    /// trajectory index, reading the atoms in each frame according to the [`AtomSelection`]. See
+    /// [`XTCReader::frames_rev`].
+    pub fn frames_rev_with_selection<'r, 'i>(
+        &'r mut self,
+        index: &'i XTCIndex,
+        frame_selection: FrameSelection,
+        atom_selection: AtomSelection,
+    ) -> FramesRev<'r, 'i> {
+        FramesRev {
+            remaining: index.len(),
+            reader: self,
+            index,
+            frame_selection,
+            atom_selection,
+            done: false,
+        }
+    }
+
+    /// Position the reader at the first frame whose time is greater than or equal to `time`,
+    /// according to an [`XTCIndex`].
+    ///
+    /// The recorded times are binary searched, which assumes they are monotonically non-decreasing
+    /// over the trajectory, as is the case for any sane simulation output.
+    ///
+    /// Returns the index of the frame that was landed on, such that the caller can confirm where
+    /// the reader ended up. If `time` lies before the first frame, the reader is positioned at
+    /// frame 0. If `time` lies beyond the last frame, [`None`] is returned.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors.
+    pub fn seek_to_time_indexed(
+        &mut self,
+        index: &XTCIndex,
+        time: f32,
+    ) -> io::Result<Option<usize>> {
+        let frame = index.frames.partition_point(|entry| entry.time < time);
+        if frame == index.len() {
+            return Ok(None);
+        }
+        self.seek_to_frame(index, frame)?;
+        Ok(Some(frame))
+    }
+}
+
+/// An iterator over the frames of a trajectory in reverse order, created by
+/// [`XTCReader::frames_rev`].
+///
+/// Yields `Result<Frame, Error>` in descending order of the trajectory index, and stops once the
+/// first included frame has been yielded.
+pub struct FramesRev<'r, 'i> {
+    reader: &'r mut XTCReader<File>,
+    index: &'i XTCIndex,
+    frame_selection: FrameSelection,
+    atom_selection: AtomSelection,
+    /// The index one past the frame that is considered next.
+    remaining: usize,
+    done: bool,
+}
+
+impl Iterator for FramesRev<'_, '_> {
+    type Item = Result<Frame, crate::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        while self.remaining > 0 {
+            self.remaining -= 1;
+            let idx = self.remaining;
+            if !self.frame_selection.is_included(idx).unwrap_or(false) {
+                continue;
+            }
+
+            let entry = &self.index.frames[idx];
+            if let Err(err) = self.reader.file.seek(SeekFrom::Start(entry.offset)) {
+                self.done = true;
+                return Some(Err(err.into()));
+            }
+            self.reader.step = idx;
+
+            let mut frame = Frame::default();
+            return match self
+                .reader
+                .read_frame_with_selection(&mut frame, &self.atom_selection)
+            {
+                Ok(()) => Some(Ok(frame)),
+                // Something went wrong...
+                Err(err) => {
+                    self.done = true;
+                    Some(Err(err))
+                }
+            };
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, test_frame, TempPath};
+    use crate::writer::XTCWriter;
+    use crate::Frame;
+
+    /// Write a small trajectory to a temporary path and return it.
+    fn write_trajectory(name: &str, nframes: usize) -> io::Result<TempPath> {
+        test_support::write_trajectory(
+            name,
+            (0..nframes as u32).map(|step| test_frame(step * 100, step as f32 * 0.5, 50)),
+        )
+    }
+
+    #[test]
+    fn build_and_seek() -> io::Result<()> {
+        let path = write_trajectory("index_build_and_seek", 5)?;
+        let mut reader = XTCReader::open(&path)?;
+        let index = reader.build_index()?;
+
+        assert_eq!(index.len(), 5);
+        assert_eq!(index.frames[0].offset, 0);
+        for (idx, entry) in index.frames.iter().enumerate() {
+            assert_eq!(entry.step, idx as u32 * 100);
+            assert_eq!(entry.time, idx as f32 * 0.5);
+            assert_eq!(entry.natoms, 50);
+        }
+
+        // The offsets must agree with the frames that are read through them.
+        let mut frame = Frame::default();
+        for target in [3, 0, 4, 2] {
+            reader.seek_to_frame(&index, target)?;
+            reader.read_frame(&mut frame)?;
+            assert_eq!(frame.step, target as u32 * 100);
+        }
+
+        // Seeking beyond the indexed range must be rejected.
+        assert!(reader.seek_to_frame(&index, 5).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn seek_by_time() -> io::Result<()> {
+        // Frames at times 0.0, 0.5, 1.0, 1.5, 2.0.
+        let path = write_trajectory("index_seek_by_time", 5)?;
+        let mut reader = XTCReader::open(&path)?;
+        let index = reader.build_index()?;
+
+        let mut frame = Frame::default();
+        for (target, expected) in [(0.6, 2), (0.5, 1), (-1.0, 0), (2.0, 4)] {
+            assert_eq!(reader.seek_to_time_indexed(&index, target)?, Some(expected));
+            reader.read_frame(&mut frame)?;
+            assert_eq!(frame.time, expected as f32 * 0.5);
+
+            // The plain scanning variant must agree with the indexed one.
+            assert_eq!(reader.seek_to_time(target)?, Some(expected));
+            reader.read_frame(&mut frame)?;
+            assert_eq!(frame.time, expected as f32 * 0.5);
+        }
+
+        // Beyond the last frame, there is nothing to seek to.
+        assert!(reader.seek_to_time_indexed(&index, 2.1)?.is_none());
+        assert!(reader.seek_to_time(2.1)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn atom_time_series() -> io::Result<()> {
+        let path = write_trajectory("index_atom_trajectory", 6)?;
+        let mut reader = XTCReader::open(&path)?;
+        let index = reader.build_index()?;
+
+        // The single-atom extraction must agree with a full decode.
+        let atom = 7;
+        let full = reader.read_all_frames()?;
+        reader.home()?;
+        let series = reader.atom_trajectory(&index, atom)?;
+        assert_eq!(series.len(), 6);
+        for (position, frame) in series.iter().zip(full.iter()) {
+            let expected = &frame.positions[atom as usize * 3..atom as usize * 3 + 3];
+            assert_eq!(position, expected);
+        }
+
+        // A frame selection thins out the series.
+        let every_other = FrameSelection::All.downsample(2.try_into().unwrap());
+        let series = reader.atom_trajectory_with_selection(&index, atom, &every_other)?;
+        assert_eq!(series.len(), 3);
+        assert_eq!(series[1], full[2].positions[atom as usize * 3..atom as usize * 3 + 3]);
+
+        // An atom beyond the trajectory must be rejected.
+        assert!(reader.atom_trajectory(&index, 50).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn reverse_iteration() -> io::Result<()> {
+        let path = write_trajectory("index_frames_rev", 5)?;
+        let mut reader = XTCReader::open(&path)?;
+        let index = reader.build_index()?;
+
+        let forward: Vec<Frame> = reader.frames().collect::<Result<_, _>>()?;
+        reader.home()?;
+        let backward: Vec<Frame> = reader.frames_rev(&index).collect::<Result<_, _>>()?;
+        assert!(backward.iter().eq(forward.iter().rev()));
+
+        // A frame selection is honored, still in descending order.
+        let every_other = FrameSelection::All.downsample(2.try_into().unwrap());
+        let steps: Vec<u32> = reader
+            .frames_rev_with_selection(&index, every_other, AtomSelection::All)
+            .map(|frame| Ok(frame?.step))
+            .collect::<io::Result<_>>()?;
+        assert_eq!(steps, [400, 200, 0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrip_and_staleness() -> io::Result<()> {
+        let path = write_trajectory("index_roundtrip", 3)?;
+        let mut reader = XTCReader::open(&path)?;
+        let index = reader.build_index()?;
+
+        let mut serialized = Vec::new();
+        index.write_to(&mut serialized)?;
+        let read_back = XTCIndex::read_from(&mut serialized.as_slice())?;
+        assert_eq!(index, read_back);
+        assert!(!read_back.is_stale(&path)?);
+
+        // Growing the trajectory must invalidate the cached index.
+        let mut writer = XTCWriter::new(std::fs::OpenOptions::new().append(true).open(&path)?);
+        writer.write_frame(&Frame {
+            positions: vec![0.0; 3 * 50],
+            precision: 1000.0,
+            ..Frame::default()
+        })?;
+        assert!(read_back.is_stale(&path)?);
+
+        Ok(())
+    }
+}
@@ -1,19 +1,51 @@
 use std::fs::File;
 use std::io::{self, Read, Seek, SeekFrom};
-use std::{cell::Cell, path::Path};
+use std::{
+    cell::Cell,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
 use glam::{Mat3, Vec3};
-use reader::read_nbytes;
+use reader::{read_nbytes, NBYTES_POSITIONS_PRELUDE};
 
 use crate::buffer::{Buffer, UnBuffered};
 use crate::reader::{
     read_boxvec, read_compressed_positions, read_f32, read_f32s, read_i32, read_u32,
 };
-use crate::selection::{AtomSelection, FrameSelection};
+use crate::selection::{AtomSelection, FrameSelection, Query};
 
 pub mod buffer;
+pub mod chain;
+pub mod error;
+pub mod index;
+#[cfg(feature = "async")]
+pub mod async_reader;
+#[cfg(all(feature = "mmap", unix))]
+pub mod mmap;
+pub mod ndx;
+pub mod parallel;
 pub mod reader;
 pub mod selection;
+pub mod stats;
+pub mod stream;
+#[cfg(test)]
+mod test_support;
+pub mod topology;
+pub mod trr;
+pub mod writer;
+
+#[cfg(feature = "async")]
+pub use async_reader::AsyncXTCReader;
+pub use chain::XTCChain;
+pub use error::Error;
+pub use index::XTCIndex;
+#[cfg(all(feature = "mmap", unix))]
+pub use mmap::XTCMmapReader;
+pub use stream::XTCStreamReader;
+pub use topology::Topology;
+pub use trr::TRRReader;
+pub use writer::XTCWriter;
 
 // See https://gitlab.com/gromacs/gromacs/-/blob/v2024.1/src/gromacs/fileio/xdrf.h?ref_type=tags#L78
 pub const XTC_1995_MAX_NATOMS: usize = 298261617;
@@ -76,15 +108,33 @@ pub struct Header {
 impl Header {
     pub const SIZE: usize = 4 * (5 + 9);
 
-    pub fn read(file: &mut impl Read) -> io::Result<Self> {
-        let magic = Magic::try_from(read_i32(file)?)
-            .map_err(|err| io::Error::other(format!("could not read header: {err}")))?;
+    pub fn read(file: &mut impl Read) -> Result<Self, Error> {
+        Self::read_impl(file, false)
+    }
+
+    /// Read a header, reinterpreting a negative stored step as its wrapped 32-bit value.
+    ///
+    /// The step field is stored as a signed 32-bit integer, which a long simulation overflows.
+    /// Where [`Header::read`] rejects such a value as [`Error::StepOutOfRange`], this accepts it
+    /// as the lower 32 bits of the true step count. See [`XTCReader::unwrap_step`].
+    pub fn read_wrapping(file: &mut impl Read) -> Result<Self, Error> {
+        Self::read_impl(file, true)
+    }
+
+    fn read_impl(file: &mut impl Read, wrapping: bool) -> Result<Self, Error> {
+        let found = read_i32(file)?;
+        let magic = Magic::try_from(found).map_err(|_| Error::BadMagic { found })?;
         let natoms: usize = read_u32(file)?
             .try_into()
             .map_err(|err| io::Error::other(format!("could not read natoms: {err}")))?;
-        let step: u32 = read_i32(file)?
-            .try_into()
-            .map_err(|err| io::Error::other(format!("could not read step: {err}")))?;
+        let found = read_i32(file)?;
+        let step: u32 = if wrapping {
+            found as u32
+        } else {
+            found
+                .try_into()
+                .map_err(|_| Error::StepOutOfRange { found })?
+        };
         let time = read_f32(file)?;
 
         // Read the frame data.
@@ -92,7 +142,13 @@ impl Header {
         let natoms_repeated = read_u32(file)?
             .try_into()
             .map_err(|err| io::Error::other(format!("could not read second natoms: {err}")))?;
-        assert_eq!(natoms, natoms_repeated);
+        if natoms != natoms_repeated {
+            return Err(Error::UnexpectedNatoms {
+                expected: natoms,
+                found: natoms_repeated,
+                frame: None,
+            });
+        }
 
         Ok(Header {
             magic,
@@ -126,6 +182,24 @@ impl Header {
     }
 }
 
+/// The metadata of a single frame, as returned by [`XTCReader::scan_header`].
+///
+/// Contrary to [`Header`], this includes the `precision` of the coordinate block, and leaves out
+/// the fields that only concern the wire layout of the frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameHeader {
+    pub natoms: usize,
+    pub step: u32,
+    /// Time in picoseconds.
+    pub time: f32,
+    pub boxvec: BoxVec,
+    /// The precision with which the coordinates are compressed.
+    ///
+    /// For very small systems (9 atoms or fewer), the positions are stored uncompressed and no
+    /// precision exists.
+    pub precision: Option<f32>,
+}
+
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct Frame {
     pub step: u32,
@@ -142,6 +216,25 @@ impl Frame {
         self.positions.chunks_exact(3).map(Vec3::from_slice)
     }
 
+    /// Returns an iterator over the positions included in an [`AtomSelection`], by reference.
+    ///
+    /// Contrary to [`Frame::coords`], no coordinate is copied out: the items borrow straight
+    /// from [`Frame::positions`], which keeps read-only analysis over many frames
+    /// allocation-free. Excluded positions are skipped, and the iteration stops at the
+    /// selection's bound, so trailing positions that a selective read left undefined are never
+    /// yielded.
+    pub fn selected_coords<'a>(
+        &'a self,
+        atoms: &'a AtomSelection,
+    ) -> impl Iterator<Item = &'a [f32; 3]> {
+        self.positions
+            .chunks_exact(3)
+            .enumerate()
+            .map_while(|(idx, pos)| atoms.is_included(idx).map(|included| (included, pos)))
+            .filter(|&(included, _)| included)
+            .map(|(_, pos)| pos.try_into().unwrap())
+    }
+
     /// Returns the number of atoms in this [`Frame`].
     pub fn natoms(&self) -> usize {
         let npos = self.positions.len();
@@ -152,6 +245,349 @@ impl Frame {
         );
         npos / 3
     }
+
+    /// Returns the box vectors _a_, _b_, _c_ of this [`Frame`] as the rows of a 3×3 array.
+    pub fn box_matrix(&self) -> [[f32; 3]; 3] {
+        // The box vectors are stored as the columns of `boxvec`.
+        self.boxvec.to_cols_array_2d()
+    }
+
+    /// Returns the box vectors _a_, _b_, _c_ of this [`Frame`] as the rows of an
+    /// [`nalgebra::Matrix3`], matching the layout of [`Frame::box_matrix`].
+    #[cfg(feature = "nalgebra")]
+    pub fn box_matrix3(&self) -> nalgebra::Matrix3<f32> {
+        // `Matrix3: From<[[f32; 3]; 3]>` treats the outer array as columns, hence the transpose.
+        nalgebra::Matrix3::from(self.box_matrix()).transpose()
+    }
+
+    /// Returns the volume of the simulation box.
+    ///
+    /// This is the determinant of the box matrix, _a_ · (_b_ × _c_), which accounts for triclinic
+    /// boxes. A frame with a zeroed box yields a volume of zero.
+    pub fn box_volume(&self) -> f32 {
+        self.boxvec.determinant().abs()
+    }
+
+    /// Returns the lengths of the box vectors _a_, _b_, _c_ and the box angles α, β, γ in
+    /// degrees.
+    ///
+    /// The angles follow the crystallographic convention: α between _b_ and _c_, β between _a_
+    /// and _c_, and γ between _a_ and _b_.
+    pub fn box_lengths_angles(&self) -> ([f32; 3], [f32; 3]) {
+        let [a, b, c] = [self.boxvec.x_axis, self.boxvec.y_axis, self.boxvec.z_axis];
+        let lengths = [a.length(), b.length(), c.length()];
+        let angle = |u: Vec3, v: Vec3| u.angle_between(v).to_degrees();
+        let angles = [angle(b, c), angle(a, c), angle(a, b)];
+        (lengths, angles)
+    }
+
+    /// Returns the geometric center of the selected atoms, or [`None`] if the selection is
+    /// empty.
+    ///
+    /// # Note
+    ///
+    /// The selection addresses the positions stored in this [`Frame`]. If the frame was read
+    /// with an [`AtomSelection`], the indices here are relative to that selection, not to the
+    /// original system.
+    pub fn center_of_geometry(&self, atom_selection: &AtomSelection) -> Option<[f32; 3]> {
+        let mut sum = Vec3::ZERO;
+        let mut count = 0;
+        for (idx, pos) in self.coords().enumerate() {
+            match atom_selection.is_included(idx) {
+                Some(true) => {
+                    sum += pos;
+                    count += 1;
+                }
+                Some(false) => {}
+                // The selection holds no atoms beyond this point.
+                None => break,
+            }
+        }
+        if count == 0 {
+            return None;
+        }
+        Some((sum / count as f32).to_array())
+    }
+
+    /// Returns the minimum and maximum corners of the axis-aligned bounding box of the selected
+    /// atoms, or [`None`] if the selection is empty.
+    ///
+    /// # Note
+    ///
+    /// The selection addresses the positions stored in this [`Frame`]. If the frame was read
+    /// with an [`AtomSelection`], the indices here are relative to that selection, not to the
+    /// original system.
+    pub fn bounding_box(&self, atom_selection: &AtomSelection) -> Option<([f32; 3], [f32; 3])> {
+        let mut min = Vec3::splat(f32::INFINITY);
+        let mut max = Vec3::splat(f32::NEG_INFINITY);
+        let mut empty = true;
+        for (idx, pos) in self.coords().enumerate() {
+            match atom_selection.is_included(idx) {
+                Some(true) => {
+                    min = min.min(pos);
+                    max = max.max(pos);
+                    empty = false;
+                }
+                Some(false) => {}
+                // The selection holds no atoms beyond this point.
+                None => break,
+            }
+        }
+        if empty {
+            return None;
+        }
+        Some((min.to_array(), max.to_array()))
+    }
+
+    /// Superpose this [`Frame`] onto a reference by an optimal rigid-body fit.
+    ///
+    /// The global rotation and translation of a trajectory obscures the internal motion that an
+    /// analysis is usually after. This function solves the Kabsch problem over the selected
+    /// atoms—through Horn's quaternion formulation, which always yields a proper rotation—and
+    /// applies the resulting transformation to _all_ coordinates of this frame. Returns the RMSD
+    /// over the selected atoms after the fit.
+    ///
+    /// An empty selection leaves the frame untouched and returns zero.
+    ///
+    /// # Note
+    ///
+    /// The selection addresses the positions stored in both frames, which must correspond atom
+    /// for atom. If the frames were read with an [`AtomSelection`], the indices here are
+    /// relative to that selection, not to the original system.
+    ///
+    /// # Panics
+    ///
+    /// If the two frames hold a different number of atoms, this function panics.
+    pub fn superpose(&mut self, reference: &Frame, atom_selection: &AtomSelection) -> f32 {
+        assert_eq!(
+            self.natoms(),
+            reference.natoms(),
+            "superposition requires frames with the same number of atoms"
+        );
+
+        // The centroids of the fitting subset, accumulated in f64 to keep the fit stable for
+        // large selections.
+        let mut center = [0.0f64; 3];
+        let mut center_ref = [0.0f64; 3];
+        let mut count = 0usize;
+        for (idx, (pos, pos_ref)) in self
+            .positions
+            .chunks_exact(3)
+            .zip(reference.positions.chunks_exact(3))
+            .enumerate()
+        {
+            match atom_selection.is_included(idx) {
+                Some(true) => {
+                    for dim in 0..3 {
+                        center[dim] += pos[dim] as f64;
+                        center_ref[dim] += pos_ref[dim] as f64;
+                    }
+                    count += 1;
+                }
+                Some(false) => {}
+                // The selection holds no atoms beyond this point.
+                None => break,
+            }
+        }
+        if count == 0 {
+            return 0.0;
+        }
+        for dim in 0..3 {
+            center[dim] /= count as f64;
+            center_ref[dim] /= count as f64;
+        }
+
+        // The covariance between the centered subsets: s[i][j] = Σ aᵢ bⱼ, with a the centered
+        // coordinates of this frame and b those of the reference.
+        let mut s = [[0.0f64; 3]; 3];
+        for (idx, (pos, pos_ref)) in self
+            .positions
+            .chunks_exact(3)
+            .zip(reference.positions.chunks_exact(3))
+            .enumerate()
+        {
+            match atom_selection.is_included(idx) {
+                Some(true) => {
+                    for i in 0..3 {
+                        for j in 0..3 {
+                            s[i][j] += (pos[i] as f64 - center[i]) * (pos_ref[j] as f64 - center_ref[j]);
+                        }
+                    }
+                }
+                Some(false) => {}
+                None => break,
+            }
+        }
+
+        // Horn's symmetric 4×4 matrix, whose dominant eigenvector is the unit quaternion of the
+        // optimal rotation. The power iteration below finds it after shifting the spectrum to be
+        // positive; the shift exceeds any eigenvalue magnitude, since those are bounded by the
+        // sum of the absolute matrix entries.
+        let k = [
+            [s[0][0] + s[1][1] + s[2][2], s[1][2] - s[2][1], s[2][0] - s[0][2], s[0][1] - s[1][0]],
+            [s[1][2] - s[2][1], s[0][0] - s[1][1] - s[2][2], s[0][1] + s[1][0], s[2][0] + s[0][2]],
+            [s[2][0] - s[0][2], s[0][1] + s[1][0], -s[0][0] + s[1][1] - s[2][2], s[1][2] + s[2][1]],
+            [s[0][1] - s[1][0], s[2][0] + s[0][2], s[1][2] + s[2][1], -s[0][0] - s[1][1] + s[2][2]],
+        ];
+        let shift = 1.0 + 2.0 * s.iter().flatten().map(|v| v.abs()).sum::<f64>();
+        let mut q = [1.0f64, 0.0, 0.0, 0.0];
+        for _ in 0..100 {
+            let mut next = [0.0f64; 4];
+            for i in 0..4 {
+                for j in 0..4 {
+                    next[i] += (k[i][j] + if i == j { shift } else { 0.0 }) * q[j];
+                }
+            }
+            let norm = next.iter().map(|v| v * v).sum::<f64>().sqrt();
+            q = next.map(|v| v / norm);
+        }
+
+        // The rotation matrix of the quaternion, which takes this frame's centered coordinates
+        // onto the reference's.
+        let [w, x, y, z] = q;
+        let r = [
+            [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - w * z), 2.0 * (x * z + w * y)],
+            [2.0 * (x * y + w * z), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - w * x)],
+            [2.0 * (x * z - w * y), 2.0 * (y * z + w * x), 1.0 - 2.0 * (x * x + y * y)],
+        ];
+
+        // Rotate every coordinate about the fit centroid and move it onto the reference's.
+        for pos in self.positions.chunks_exact_mut(3) {
+            let p = [
+                pos[0] as f64 - center[0],
+                pos[1] as f64 - center[1],
+                pos[2] as f64 - center[2],
+            ];
+            for i in 0..3 {
+                pos[i] = (r[i][0] * p[0] + r[i][1] * p[1] + r[i][2] * p[2] + center_ref[i]) as f32;
+            }
+        }
+
+        // The RMSD over the fitting subset after the transformation.
+        let mut sum = 0.0f64;
+        for (idx, (pos, pos_ref)) in self
+            .positions
+            .chunks_exact(3)
+            .zip(reference.positions.chunks_exact(3))
+            .enumerate()
+        {
+            match atom_selection.is_included(idx) {
+                Some(true) => {
+                    for dim in 0..3 {
+                        let diff = pos[dim] as f64 - pos_ref[dim] as f64;
+                        sum += diff * diff;
+                    }
+                }
+                Some(false) => {}
+                None => break,
+            }
+        }
+        (sum / count as f64).sqrt() as f32
+    }
+
+    /// Wrap the selected atoms back into the primary unit cell.
+    ///
+    /// The inverse of [`Frame::make_whole`]: after a transform, coordinates may drift outside
+    /// the box, and visualization and neighbor searching want them mapped back in. Each selected
+    /// atom is taken to fractional (box) coordinates, reduced into the `[0, 1)` range, and taken
+    /// back to Cartesian, which handles the shear of a triclinic box correctly. Unselected atoms
+    /// and the box itself are left untouched, as is a frame with a zeroed box, since no periodic
+    /// images exist to wrap between.
+    pub fn wrap_into_box(&mut self, atoms: &AtomSelection) {
+        if self.boxvec.determinant() == 0.0 {
+            return;
+        }
+        let inverse = self.boxvec.inverse();
+
+        for (idx, pos) in self.positions.chunks_exact_mut(3).enumerate() {
+            match atoms.is_included(idx) {
+                Some(true) => {}
+                Some(false) => continue,
+                None => break,
+            }
+            let fractional = inverse * Vec3::from_slice(pos);
+            // Reduce each fractional component into [0, 1). The subtraction of the floor, rather
+            // than a remainder, keeps negative coordinates on the right side.
+            let wrapped = self.boxvec * (fractional - fractional.floor());
+            pos.copy_from_slice(&wrapped.to_array());
+        }
+    }
+
+    /// Shift atoms by box vectors such that each molecule is contiguous again.
+    ///
+    /// The coordinates in an xtc file are wrapped into the primary box, which splits molecules
+    /// that straddle a periodic boundary and ruins distance calculations over them. Given the
+    /// bonds between atoms, this function walks each connected component and moves every atom to
+    /// the periodic image closest to its bonded predecessor. Triclinic boxes are handled through
+    /// the box matrix; the box itself is left untouched.
+    ///
+    /// Atoms without any bonds are left where they are. A frame with a zeroed box is returned
+    /// unchanged, since no periodic images exist to shift between.
+    ///
+    /// # Panics
+    ///
+    /// If a bond refers to an atom index beyond the frame, this function panics.
+    pub fn make_whole(&mut self, bonds: &[(u32, u32)]) {
+        let natoms = self.natoms();
+        if self.boxvec.determinant() == 0.0 {
+            return;
+        }
+        let inverse = self.boxvec.inverse();
+
+        let mut adjacency = vec![Vec::new(); natoms];
+        for &(i, j) in bonds {
+            let (i, j) = (i as usize, j as usize);
+            assert!(
+                i < natoms && j < natoms,
+                "the bond ({i}, {j}) refers to an atom outside the frame (natoms = {natoms})"
+            );
+            adjacency[i].push(j);
+            adjacency[j].push(i);
+        }
+
+        let mut visited = vec![false; natoms];
+        let mut stack = Vec::new();
+        for root in 0..natoms {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            stack.push(root);
+            while let Some(i) = stack.pop() {
+                let pi = Vec3::from_slice(&self.positions[i * 3..i * 3 + 3]);
+                for &j in &adjacency[i] {
+                    if visited[j] {
+                        continue;
+                    }
+                    visited[j] = true;
+                    let pj = Vec3::from_slice(&self.positions[j * 3..j * 3 + 3]);
+                    // Move atom j to the periodic image nearest to atom i: round the difference
+                    // in fractional (box) coordinates to whole numbers of box vectors.
+                    let shift = self.boxvec * (inverse * (pj - pi)).round();
+                    let moved = pj - shift;
+                    self.positions[j * 3..j * 3 + 3].copy_from_slice(&moved.to_array());
+                    stack.push(j);
+                }
+            }
+        }
+    }
+}
+
+/// The result of a call to [`XTCReader::read_frame_resilient`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameReadOutcome {
+    /// A frame was read successfully.
+    Read,
+    /// A corrupt stretch of `bytes` bytes was skipped over.
+    ///
+    /// The reader is positioned at the next plausible frame header, which a subsequent call will
+    /// attempt to read.
+    Skipped {
+        /// The number of bytes that were skipped.
+        bytes: u64,
+    },
+    /// The end of the trajectory was reached, and no more frames could be recovered.
+    Eof,
 }
 
 /// Calculate the xdr padding for some number of bytes.
@@ -162,7 +598,7 @@ pub fn padding(n: usize) -> usize {
 
 /// Read the positions in a frame after the header.
 ///
-/// If successful, returns the number of compressed bytes that were read.
+/// If successful, returns the number of bytes that were consumed from the reader.
 ///
 /// Internal use.
 #[doc(hidden)]
@@ -180,7 +616,7 @@ pub fn read_positions<'s, 'r, B: buffer::Buffered<'s, 'r, R>, R: Read>(
     // Resize the positions array for the selected number of atoms.
     frame.positions.resize(natoms_selected * 3, f32::NAN);
     frame.precision = read_f32(file)?;
-    read_compressed_positions::<B, R>(
+    let nbytes = read_compressed_positions::<B, R>(
         file,
         header_natoms,
         &mut frame.positions,
@@ -188,18 +624,333 @@ pub fn read_positions<'s, 'r, B: buffer::Buffered<'s, 'r, R>, R: Read>(
         scratch,
         atom_selection,
         magic,
-    )
+    )?;
+    Ok(nbytes + std::mem::size_of::<f32>()) // Account for the precision value as well.
+}
+
+/// A [`Frame`] with its coordinates and box widened to `f64`, created by
+/// [`XTCReader::read_frame_f64`].
+///
+/// The compressed coordinates of an xtc frame lie on an integer grid scaled by the frame
+/// precision, so the widened values carry the stored integers exactly. When millions of positions
+/// are accumulated—for a density grid, say—summing these avoids the `f32` accumulation error
+/// without changing what was read from the file.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FrameF64 {
+    pub step: u32,
+    /// Time in picoseconds.
+    pub time: f32,
+    pub boxvec: glam::DMat3,
+    pub precision: f32,
+    pub positions: Vec<f64>,
+}
+
+/// The units in which an [`XTCReader`] reports coordinates.
+///
+/// Xtc files store coordinates in nanometers. Downstream tools commonly expect Ångström, and
+/// rather than converting every coordinate by hand after reading, the conversion can be applied
+/// during decode by setting [`XTCReader::units`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Units {
+    /// Nanometers, the unit in which xtc files store coordinates.
+    #[default]
+    Nm,
+    /// Ångström. Decoded positions and box vectors are multiplied by ten, such that derived
+    /// quantities like [`Frame::box_volume`] are consistently in Å³.
+    Angstrom,
+}
+
+impl Units {
+    /// The factor from the stored nanometers to these units.
+    pub fn factor(&self) -> f32 {
+        match self {
+            Units::Nm => 1.0,
+            Units::Angstrom => 10.0,
+        }
+    }
+}
+
+/// A dense `(n_frames, n_atoms, 3)` array of coordinates, created by
+/// [`XTCReader::read_to_array`].
+///
+/// The coordinates are stored contiguously in row-major order, such that the flat slice returned
+/// by [`CoordinateArray::as_slice`] can be handed to numerical libraries without copying.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoordinateArray {
+    data: Vec<f32>,
+    nframes: usize,
+    natoms: usize,
+}
+
+impl CoordinateArray {
+    /// Returns the shape of this array as `(n_frames, n_atoms, 3)`.
+    pub fn shape(&self) -> (usize, usize, usize) {
+        (self.nframes, self.natoms, 3)
+    }
+
+    /// Returns the coordinates as a flat slice in row-major `(frame, atom, dim)` order.
+    pub fn as_slice(&self) -> &[f32] {
+        &self.data
+    }
+
+    /// Returns the positions of a single frame as a flat slice of `n_atoms * 3` values.
+    ///
+    /// # Panics
+    ///
+    /// If `frame` lies beyond the number of frames in this array, this function panics.
+    pub fn frame(&self, frame: usize) -> &[f32] {
+        assert!(
+            frame < self.nframes,
+            "the frame index ({frame}) is outside the defined range of the array (..{})",
+            self.nframes
+        );
+        &self.data[frame * self.natoms * 3..(frame + 1) * self.natoms * 3]
+    }
+
+    /// Converts this [`CoordinateArray`] into an [`ndarray::Array3`] of shape
+    /// `(n_frames, n_atoms, 3)`.
+    ///
+    /// The coordinates are already laid out in the row-major order `Array3` expects, so the
+    /// backing storage is handed over without copying.
+    #[cfg(feature = "ndarray")]
+    pub fn into_array3(self) -> ndarray::Array3<f32> {
+        ndarray::Array3::from_shape_vec((self.nframes, self.natoms, 3), self.data)
+            .expect("the shape of a CoordinateArray always matches its storage")
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl From<CoordinateArray> for ndarray::Array3<f32> {
+    fn from(array: CoordinateArray) -> Self {
+        array.into_array3()
+    }
+}
+
+impl std::ops::Index<[usize; 3]> for CoordinateArray {
+    type Output = f32;
+
+    fn index(&self, [frame, atom, dim]: [usize; 3]) -> &f32 {
+        let (nframes, natoms, ndims) = self.shape();
+        assert!(
+            frame < nframes && atom < natoms && dim < ndims,
+            "the index [{frame}, {atom}, {dim}] is outside the shape of the array \
+            ({nframes}, {natoms}, {ndims})"
+        );
+        &self.data[(frame * natoms + atom) * 3 + dim]
+    }
+}
+
+/// A progress report emitted while reading a trajectory.
+///
+/// See [`XTCReader::on_progress`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// The number of bytes that have been consumed from the reader by frame reads so far.
+    pub bytes_read: u64,
+    /// The total length of the trajectory file in bytes, if known.
+    ///
+    /// This is taken from the file metadata by [`XTCReader::open`], and is [`None`] for readers
+    /// over other sources.
+    pub file_len: Option<u64>,
+    /// The number of frames that have been read so far.
+    pub frames_read: usize,
+}
+
+/// The callback registered through [`XTCReader::on_progress`].
+type ProgressCallback = Box<dyn FnMut(Progress) + Send>;
+
+/// The callback registered through [`XTCReader::with_transform`].
+type TransformCallback = Box<dyn FnMut(&mut Frame) + Send>;
+
+/// The on-disk footprint of the last frame that was read.
+///
+/// See [`XTCReader::frame_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameStats {
+    /// The byte length of the coordinate block as stored, including its prelude and padding.
+    pub compressed_size: usize,
+    /// The total number of bytes the frame occupies on disk, including its header.
+    pub total_size: usize,
+}
+
+/// A running summary of the decode work a reader has performed.
+///
+/// See [`XTCReader::stats`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReadStats {
+    /// The number of frames that have been read.
+    pub frames: usize,
+    /// The number of atoms decoded into frames, summed over the reads.
+    ///
+    /// With an atom selection in place, only the selected atoms count.
+    pub selected_atoms: u64,
+    /// The number of compressed coordinate-block bytes consumed, including preludes and padding.
+    pub compressed_bytes: u64,
+    /// The wall-clock time spent decoding frames.
+    ///
+    /// A transform registered through [`XTCReader::with_transform`] runs outside of this clock,
+    /// so the value reflects the decode work alone.
+    pub decode_time: Duration,
 }
 
-#[derive(Debug, Clone)]
 pub struct XTCReader<R> {
     pub file: R,
     pub step: usize,
+    /// The units in which decoded coordinates and box vectors are reported.
+    ///
+    /// The stored [`Frame::precision`] is left untouched by this setting: it remains the
+    /// points-per-nanometer value from the file.
+    pub units: Units,
+    /// The default [`AtomSelection`] applied by the plain reading functions.
+    ///
+    /// This field is private so that it can only be set through
+    /// [`XTCReader::set_atom_selection`], which validates it against the trajectory.
+    atom_selection: AtomSelection,
+    /// Whether decoded frames are scanned for non-finite coordinates, see
+    /// [`XTCReader::check_finite`].
+    check_finite: bool,
+    /// The number of bytes that have been consumed by frame reads, reported in [`Progress`].
+    bytes_read: u64,
+    /// The total length of the trajectory file in bytes, if known.
+    file_len: Option<u64>,
+    /// The callback to report progress to, if one is registered.
+    on_progress: Option<ProgressCallback>,
+    /// The transform applied to every decoded frame, if one is registered.
+    transform: Option<TransformCallback>,
+    /// Whether steps beyond `i32::MAX` are reconstructed, see [`XTCReader::unwrap_step`].
+    unwrap_step: bool,
+    /// The natoms value every frame is expected to match, see
+    /// [`XTCReader::expect_constant_natoms`]. `None` either means the check is disabled, or that
+    /// no frame has been read yet.
+    expected_natoms: Option<usize>,
+    /// Whether the natoms consistency check is enabled.
+    expect_constant_natoms: bool,
+    /// The raw step and time of the last frame read, used to detect 32-bit step wraps.
+    last_raw_step: Option<(u32, f32)>,
+    /// The number of detected 32-bit step wraps since the start of the trajectory.
+    step_wraps: u64,
+    /// The on-disk footprint of the last frame read, see [`XTCReader::frame_stats`].
+    frame_stats: Option<FrameStats>,
+    /// The decode work performed so far, see [`XTCReader::stats`].
+    read_stats: ReadStats,
+    /// The header cached by [`XTCReader::peek_header`], tagged with the position it was read at.
+    peeked: Option<(u64, FrameHeader)>,
+}
+
+impl<R: std::fmt::Debug> std::fmt::Debug for XTCReader<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("XTCReader")
+            .field("file", &self.file)
+            .field("step", &self.step)
+            .field("units", &self.units)
+            .field("atom_selection", &self.atom_selection)
+            .field("check_finite", &self.check_finite)
+            .field("bytes_read", &self.bytes_read)
+            .field("file_len", &self.file_len)
+            .field("on_progress", &self.on_progress.as_ref().map(|_| ".."))
+            .field("transform", &self.transform.as_ref().map(|_| ".."))
+            .field("unwrap_step", &self.unwrap_step)
+            .field("expect_constant_natoms", &self.expect_constant_natoms)
+            .field("frame_stats", &self.frame_stats)
+            .field("read_stats", &self.read_stats)
+            .finish()
+    }
+}
+
+impl<R: Clone> Clone for XTCReader<R> {
+    /// The registered progress and transform callbacks are not cloned; the clone starts without
+    /// them.
+    fn clone(&self) -> Self {
+        Self {
+            file: self.file.clone(),
+            step: self.step,
+            units: self.units,
+            atom_selection: self.atom_selection.clone(),
+            check_finite: self.check_finite,
+            bytes_read: self.bytes_read,
+            file_len: self.file_len,
+            on_progress: None,
+            transform: None,
+            unwrap_step: self.unwrap_step,
+            last_raw_step: self.last_raw_step,
+            step_wraps: self.step_wraps,
+            expected_natoms: self.expected_natoms,
+            expect_constant_natoms: self.expect_constant_natoms,
+            frame_stats: self.frame_stats,
+            read_stats: self.read_stats,
+            peeked: self.peeked,
+        }
+    }
 }
 
-impl XTCReader<std::fs::File> {
+impl XTCReader<File> {
     pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
-        let file = std::fs::File::open(path)?;
+        let file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let mut reader = Self::new(file);
+        reader.file_len = Some(file_len);
+        Ok(reader)
+    }
+}
+
+impl XTCReader<Box<dyn Read>> {
+    /// Open a trajectory that may be compressed as a whole, such as `traj.xtc.gz`.
+    ///
+    /// Archived trajectories often ship gzip- or zstd-compressed, and this saves the detour of
+    /// decompressing to a temporary file first. The compression is detected from the magic bytes
+    /// at the start of the file—not from its extension—and the file is wrapped in a streaming
+    /// decoder accordingly. Gzip and zstd are recognized behind the `gzip` and `zstd` features; a
+    /// file that starts with neither magic is read as a plain xtc file.
+    ///
+    /// # Note
+    ///
+    /// The decoders cannot seek, so the returned reader only offers the sequential reading
+    /// functions; the seek-based API (frame selections, seeking to a time, indexing) is not
+    /// available on it. For repeated random access, decompress to a file once instead.
+    ///
+    /// # Errors
+    ///
+    /// If the file is compressed in a format whose feature is not enabled, an error saying so is
+    /// returned. Reader errors are passed through.
+    pub fn open_maybe_compressed<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let mut magic = [0u8; 4];
+        let mut nread = 0;
+        while nread < magic.len() {
+            match file.read(&mut magic[nread..])? {
+                0 => break,
+                n => nread += n,
+            }
+        }
+        file.seek(SeekFrom::Start(0))?;
+
+        let file: Box<dyn Read> = if magic[..2] == [0x1f, 0x8b] {
+            #[cfg(feature = "gzip")]
+            {
+                Box::new(flate2::read::MultiGzDecoder::new(file))
+            }
+            #[cfg(not(feature = "gzip"))]
+            {
+                return Err(io::Error::other(
+                    "the file is gzip-compressed, but molly was compiled without the `gzip` \
+                    feature",
+                ));
+            }
+        } else if magic == [0x28, 0xb5, 0x2f, 0xfd] {
+            #[cfg(feature = "zstd")]
+            {
+                Box::new(zstd::stream::read::Decoder::new(file)?)
+            }
+            #[cfg(not(feature = "zstd"))]
+            {
+                return Err(io::Error::other(
+                    "the file is zstd-compressed, but molly was compiled without the `zstd` \
+                    feature",
+                ));
+            }
+        } else {
+            Box::new(file)
+        };
         Ok(Self::new(file))
     }
 }
@@ -209,14 +960,153 @@ impl<R: Read> XTCReader<R> {
         Self {
             file: reader,
             step: 0,
+            units: Units::default(),
+            atom_selection: AtomSelection::All,
+            check_finite: false,
+            bytes_read: 0,
+            file_len: None,
+            on_progress: None,
+            transform: None,
+            unwrap_step: false,
+            last_raw_step: None,
+            step_wraps: 0,
+            expected_natoms: None,
+            expect_constant_natoms: false,
+            frame_stats: None,
+            read_stats: ReadStats::default(),
+            peeked: None,
         }
     }
 
+    /// Register a callback that is called once for every frame that is read.
+    ///
+    /// When reading a large trajectory in an interactive tool, this makes it possible to show a
+    /// progress bar rather than a frozen terminal. The reported [`Progress`] carries the number of
+    /// bytes consumed by frame reads, the file length (known when the reader was created through
+    /// [`XTCReader::open`]), and the number of frames read. Registering a callback is purely
+    /// observational and does not change any read results.
+    pub fn on_progress(&mut self, callback: impl FnMut(Progress) + Send + 'static) {
+        self.on_progress = Some(Box::new(callback));
+    }
+
+    /// Register a transform that is applied to every decoded frame.
+    ///
+    /// The closure runs after decoding, the atom selection, and the unit conversion, but before
+    /// the finite-coordinate check of [`XTCReader::check_finite`]. Recentering every frame on a
+    /// selection's centroid, or applying a fixed rotation, thus happens as the frames stream in,
+    /// without a second pass:
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let mut reader = molly::XTCReader::open("trajectory.xtc")?;
+    /// reader.with_transform(|frame| {
+    ///     let [cx, cy, cz] = frame
+    ///         .center_of_geometry(&molly::selection::AtomSelection::All)
+    ///         .unwrap_or_default();
+    ///     for position in frame.positions.chunks_exact_mut(3) {
+    ///         position[0] -= cx;
+    ///         position[1] -= cy;
+    ///         position[2] -= cz;
+    ///     }
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Header-only scans such as [`XTCReader::scan_header`] are unaffected, since no frame is
+    /// decoded there.
+    pub fn with_transform(&mut self, f: impl FnMut(&mut Frame) + Send + 'static) {
+        self.transform = Some(Box::new(f));
+    }
+
+    /// Returns the on-disk footprint of the last frame that was read, if any.
+    ///
+    /// The compressed size covers the coordinate block as stored—including its prelude, byte
+    /// count, and padding—and the total size adds the frame header on top. Summed over an
+    /// all-frames read, the total sizes add up to the file length, which makes this the value to
+    /// look at when profiling where the bytes of a trajectory go. [`XTCReader::home`] clears the
+    /// value.
+    pub fn frame_stats(&self) -> Option<FrameStats> {
+        self.frame_stats
+    }
+
+    /// Returns a summary of the decode work this reader has performed.
+    ///
+    /// The counters accumulate over every frame read: the number of frames, the number of atoms
+    /// decoded into them, the compressed coordinate-block bytes consumed, and the wall-clock time
+    /// spent decoding. After reading a trajectory, this is the value to quote when comparing
+    /// throughput against other readers. The accounting is always on—it amounts to a few counter
+    /// increments per frame—and [`XTCReader::home`] resets it.
+    pub fn stats(&self) -> ReadStats {
+        self.read_stats
+    }
+
+    /// Scan every decoded frame for NaN and infinite coordinates.
+    ///
+    /// A partially corrupted compressed block can decode to absurd coordinate values that
+    /// silently poison downstream averages. With this check enabled, a frame holding a
+    /// non-finite coordinate is returned as an [`Error::NonFiniteCoords`] identifying the first
+    /// offending atom. The check is disabled by default, since it costs a scan over the
+    /// positions of every frame.
+    pub fn check_finite(&mut self, check: bool) {
+        self.check_finite = check;
+    }
+
+    /// Reconstruct a monotonic step count across the 32-bit overflow of the stored step.
+    ///
+    /// The step field of an xtc frame is stored as a signed 32-bit integer, which a
+    /// long-running simulation overflows. With this mode enabled, a frame whose stored step is
+    /// negative is accepted as the wrapped lower 32 bits of the true step count rather than
+    /// being rejected as [`Error::StepOutOfRange`], and a wrap—a raw step below its predecessor
+    /// while the time keeps increasing—adds another 2^32 to the count reported by
+    /// [`XTCReader::unwrapped_step`]. [`Frame::step`] keeps carrying the raw on-disk value.
+    ///
+    /// # Note
+    ///
+    /// The detection is sequential: it assumes the frames are read in file order.
+    /// [`XTCReader::home`] resets it. The mode is disabled by default.
+    pub fn unwrap_step(&mut self, unwrap: bool) {
+        self.unwrap_step = unwrap;
+    }
+
+    /// Returns the reconstructed monotonic step of the last frame that was read, if any.
+    ///
+    /// This only differs from the raw [`Frame::step`] once the stored 32-bit step has wrapped.
+    /// See [`XTCReader::unwrap_step`].
+    pub fn unwrapped_step(&self) -> Option<u64> {
+        self.last_raw_step
+            .map(|(raw, _)| (self.step_wraps << 32) + raw as u64)
+    }
+
+    /// Require every frame to declare the same number of atoms.
+    ///
+    /// A sudden change in the per-frame natoms field is a common sign of a corrupt file, or of
+    /// trajectories of different systems accidentally concatenated into one. With this check
+    /// enabled, the natoms value of the first frame read is recorded, and a later frame that
+    /// disagrees is rejected as [`Error::UnexpectedNatoms`] identifying the offending frame,
+    /// rather than silently producing misaligned coordinates. The check only looks at the
+    /// header field, so it costs nothing. It is disabled by default, and [`XTCReader::home`]
+    /// forgets the recorded value.
+    pub fn expect_constant_natoms(&mut self, expect: bool) {
+        self.expect_constant_natoms = expect;
+    }
+
+    /// Returns the default [`AtomSelection`] applied by the plain reading functions.
+    ///
+    /// This is [`AtomSelection::All`] unless one was set through
+    /// [`XTCReader::set_atom_selection`].
+    pub fn atom_selection(&self) -> &AtomSelection {
+        &self.atom_selection
+    }
+
     /// Read the header at the start of a frame.
     ///
     /// Assumes the internal reader is at the start of a new frame header.
-    pub fn read_header(&mut self) -> io::Result<Header> {
-        Header::read(&mut self.file)
+    pub fn read_header(&mut self) -> Result<Header, Error> {
+        match self.unwrap_step {
+            false => Header::read(&mut self.file),
+            true => Header::read_wrapping(&mut self.file),
+        }
     }
 
     /// Read a small number of uncompressed positions.
@@ -265,20 +1155,67 @@ impl<R: Read> XTCReader<R> {
         Ok(buf.len() * std::mem::size_of::<f32>())
     }
 
+    /// Returns an iterator over the remaining frames in the trajectory.
+    ///
+    /// The iterator yields `io::Result<Frame>` and stops cleanly at the end of the trajectory,
+    /// which makes it easy to plug a reader into iterator chains:
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let mut reader = molly::XTCReader::open("trajectory.xtc")?;
+    /// let times: Vec<f32> = reader.frames().map(|frame| Ok(frame?.time)).collect::<std::io::Result<_>>()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// To only read a subset of the atoms in each frame, see
+    /// [`XTCReader::frames_with_selection`]. For selections over the frames themselves, see
+    /// [`XTCReader::read_frames`].
+    pub fn frames(&mut self) -> Frames<'_, R> {
+        self.frames_with_selection(AtomSelection::All)
+    }
+
+    /// Returns an iterator over the remaining frames in the trajectory, reading the atoms in each
+    /// frame according to an [`AtomSelection`].
+    ///
+    /// See [`XTCReader::frames`].
+    pub fn frames_with_selection(&mut self, atom_selection: AtomSelection) -> Frames<'_, R> {
+        Frames {
+            reader: self,
+            atom_selection,
+            done: false,
+        }
+    }
+
+    /// Returns an iterator over the frames matched by a [`Query`], honoring both its frame and
+    /// its atom selection.
+    ///
+    /// Unselected frames are skipped over without decoding their coordinates, and each yielded
+    /// frame holds the atoms selected by the query. Frame indices are counted from the current
+    /// position of the reader. [`Query::all`] reproduces the behavior of [`XTCReader::frames`].
+    pub fn read(&mut self, query: &Query) -> QueryFrames<'_, R> {
+        QueryFrames {
+            reader: self,
+            query: query.clone(),
+            idx: 0,
+            done: false,
+        }
+    }
+
     /// A convenience function to read all frames in a trajectory.
     ///
     /// It is likely more efficient to use [`XTCReader::read_frame`] if you are only interested in
     /// the values of a single frame at a time.
-    pub fn read_all_frames(&mut self) -> io::Result<Box<[Frame]>> {
+    pub fn read_all_frames(&mut self) -> Result<Box<[Frame]>, Error> {
         let mut frames = Vec::new();
         loop {
             let mut frame = Frame::default();
             if let Err(err) = self.read_frame(&mut frame) {
-                match err.kind() {
+                match err {
                     // We have found the end of the file. No more frames, we're done.
-                    io::ErrorKind::UnexpectedEof => break,
+                    Error::Truncated => break,
                     // Something else went wrong...
-                    _ => Err(err)?,
+                    err => Err(err)?,
                 }
             }
             frames.push(frame);
@@ -287,8 +1224,297 @@ impl<R: Read> XTCReader<R> {
     }
 
     /// Reads and returns a [`Frame`] and advances one step.
-    pub fn read_frame(&mut self, frame: &mut Frame) -> io::Result<()> {
-        self.read_frame_with_selection(frame, &AtomSelection::All)
+    ///
+    /// The atoms are read according to the selection set through
+    /// [`XTCReader::set_atom_selection`], which is [`AtomSelection::All`] by default.
+    pub fn read_frame(&mut self, frame: &mut Frame) -> Result<(), Error> {
+        // Take the stored selection out to appease the borrow checker. The reading functions do
+        // not touch it, so it is put back untouched.
+        let atom_selection = std::mem::take(&mut self.atom_selection);
+        let result = self.read_frame_with_selection(frame, &atom_selection);
+        self.atom_selection = atom_selection;
+        result
+    }
+
+    /// Read the next frame into `frame`, reusing its allocations.
+    ///
+    /// Returns `false` once the end of the trajectory is reached, such that a whole trajectory can
+    /// be streamed through a single [`Frame`] without any per-frame allocation:
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let mut reader = molly::XTCReader::open("trajectory.xtc")?;
+    /// let mut frame = molly::Frame::default();
+    /// while reader.read_frame_into(&mut frame)? {
+    ///     // Do something with the frame.
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The positions buffer of `frame` is resized to the selected number of atoms, which only
+    /// allocates when the number of atoms grows beyond its capacity. The usual selection invariant
+    /// applies: positions beyond the current selection are undefined.
+    pub fn read_frame_into(&mut self, frame: &mut Frame) -> Result<bool, Error> {
+        let atom_selection = std::mem::take(&mut self.atom_selection);
+        let result = self.read_frame_into_with_selection(frame, &atom_selection);
+        self.atom_selection = atom_selection;
+        result
+    }
+
+    /// Read the next frame into `frame` according to an [`AtomSelection`], reusing its
+    /// allocations.
+    ///
+    /// See [`XTCReader::read_frame_into`].
+    pub fn read_frame_into_with_selection(
+        &mut self,
+        frame: &mut Frame,
+        atom_selection: &AtomSelection,
+    ) -> Result<bool, Error> {
+        match self.read_frame_with_selection(frame, atom_selection) {
+            Ok(()) => Ok(true),
+            // We have found the end of the file. No more frames, we're done.
+            Err(Error::Truncated) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Read the next frame, widening its coordinates and box to `f64`.
+    ///
+    /// The compressed coordinates lie on an integer grid scaled by the frame precision, so the
+    /// grid is recovered exactly from the decoded `f32` values and divided out in `f64`; nothing
+    /// beyond the stored integers is invented. Small-system frames store raw floats, which widen
+    /// losslessly as they are. The atoms are read according to the selection set through
+    /// [`XTCReader::set_atom_selection`], and the configured [`Units`] apply.
+    ///
+    /// Returns [`None`] once the end of the trajectory is reached.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors.
+    pub fn read_frame_f64(&mut self) -> Result<Option<FrameF64>, Error> {
+        let mut frame = Frame::default();
+        if !self.read_frame_into(&mut frame)? {
+            return Ok(None);
+        }
+
+        // The grid spacing in the reported units: `value * scale` is the stored integer.
+        let scale = frame.precision / self.units.factor();
+        // Small-system frames store raw floats and carry no precision, so they are widened
+        // verbatim.
+        let on_grid = frame.precision > 0.0;
+        let positions = frame
+            .positions
+            .iter()
+            .map(|&value| {
+                if on_grid {
+                    (value * scale).round() as f64 / scale as f64
+                } else {
+                    value as f64
+                }
+            })
+            .collect();
+
+        Ok(Some(FrameF64 {
+            step: frame.step,
+            time: frame.time,
+            boxvec: frame.boxvec.as_dmat3(),
+            precision: frame.precision,
+            positions,
+        }))
+    }
+
+    /// Read up to `n` frames, appending their coordinates contiguously to `buf`.
+    ///
+    /// The coordinates are laid out `[frame][atom][xyz]`, such that a batch can be handed to a
+    /// GPU upload or similar bulk consumer in one copy, without per-frame intermediaries. The
+    /// buffer is grown to its final size up front, based on the selected atom count of the first
+    /// frame in the batch. The atoms are read according to the selection set through
+    /// [`XTCReader::set_atom_selection`].
+    ///
+    /// Returns the number of frames that were actually read, which is less than `n` once the end
+    /// of the trajectory is reached.
+    pub fn read_frames_into(&mut self, n: usize, buf: &mut Vec<f32>) -> Result<usize, Error> {
+        let mut frame = Frame::default();
+        let mut count = 0;
+        while count < n {
+            if !self.read_frame_into(&mut frame)? {
+                break;
+            }
+            if count == 0 {
+                // Preallocate for the full batch, using the first frame's selected atom count.
+                buf.reserve(n * frame.positions.len());
+            }
+            buf.extend_from_slice(&frame.positions);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Consume the next frame without decoding its positions.
+    ///
+    /// The frame's bytes are read and discarded, so no seeking is involved. Returns `false` once
+    /// the end of the trajectory is reached.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors. A trajectory that ends partway through
+    /// a frame is an error, not a clean end.
+    pub fn skip_frame(&mut self) -> Result<bool, Error> {
+        let file = &mut self.file;
+        let header = match Header::read(file) {
+            Ok(header) => header,
+            Err(Error::Truncated) => return Ok(false),
+            Err(err) => return Err(err),
+        };
+
+        let discard = if header.natoms <= 9 {
+            // The positions are uncompressed, and their size follows from the atom count.
+            header.natoms as u64 * 3 * 4
+        } else {
+            // Discard the precision and the remainder of the prelude, then the stored number of
+            // compressed bytes tells us how much is left of this frame.
+            stream::discard_bytes(file, (4 + NBYTES_POSITIONS_PRELUDE) as u64)?;
+            let nbytes = read_nbytes(file, header.magic)?;
+            (nbytes + padding(nbytes)) as u64
+        };
+        stream::discard_bytes(file, discard)?;
+
+        self.step += 1;
+        Ok(true)
+    }
+
+    /// Write the frames selected by `frames` to `out`, keeping only the atoms selected by
+    /// `atoms`.
+    ///
+    /// This covers the common "thin out and subset, save to a smaller file" workflow in one call.
+    /// Frames are streamed through a single reused buffer, so the whole trajectory is never held
+    /// in memory. Unselected frames are skipped over without decoding. Frame indices are counted
+    /// from the current position of the reader.
+    ///
+    /// If successful, returns the number of frames that were written.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader and writer errors.
+    pub fn extract_to<W: io::Write>(
+        &mut self,
+        out: &mut XTCWriter<W>,
+        atoms: &AtomSelection,
+        frames: &FrameSelection,
+    ) -> Result<usize, Error> {
+        let mut frame = Frame::default();
+        let mut written = 0;
+        let mut idx = 0;
+        loop {
+            let more = match frames.is_included(idx) {
+                // The selection holds no frames beyond this point.
+                None => break,
+                Some(false) => self.skip_frame()?,
+                Some(true) => {
+                    let read = self.read_frame_into_with_selection(&mut frame, atoms)?;
+                    if read {
+                        out.write_frame(&frame)?;
+                        written += 1;
+                    }
+                    read
+                }
+            };
+            if !more {
+                break;
+            }
+            idx += 1;
+        }
+        Ok(written)
+    }
+
+    /// Split the trajectory into standalone files of `per_file` frames each.
+    ///
+    /// For distributed processing, workers each want to own a shard of a long trajectory. The
+    /// frames are streamed from the current position and rewritten into consecutive files, whose
+    /// paths are produced by `out_pattern` from the chunk index. Each output is a valid xtc file
+    /// of its own. The last chunk may hold fewer than `per_file` frames.
+    ///
+    /// Returns the written paths along with the number of frames each holds.
+    ///
+    /// # Panics
+    ///
+    /// If `per_file` is zero, this function panics.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader and writer errors.
+    pub fn split_by_frames(
+        &mut self,
+        per_file: usize,
+        out_pattern: impl Fn(usize) -> PathBuf,
+    ) -> Result<Vec<(PathBuf, usize)>, Error> {
+        assert!(per_file > 0, "cannot split into chunks of zero frames");
+
+        let mut chunks: Vec<(PathBuf, usize)> = Vec::new();
+        let mut out = None;
+        let mut frame = Frame::default();
+        let mut nread = 0;
+        while self.read_frame_into(&mut frame)? {
+            if nread % per_file == 0 {
+                let path = out_pattern(chunks.len());
+                out = Some(XTCWriter::create(&path)?);
+                chunks.push((path, 0));
+            }
+            // The writer exists here: a chunk was just started, or this frame continues one.
+            out.as_mut().unwrap().write_frame(&frame)?;
+            chunks.last_mut().unwrap().1 += 1;
+            nread += 1;
+        }
+        Ok(chunks)
+    }
+
+    /// Split the trajectory into standalone files at fixed time boundaries.
+    ///
+    /// The time axis is divided into consecutive windows of `interval`, counted from the time of
+    /// the first frame, and each window's frames are rewritten into a file of their own. The
+    /// paths are produced by `out_pattern` from the chunk index; windows that hold no frames
+    /// produce no file. Each output is a valid xtc file of its own.
+    ///
+    /// Returns the written paths along with the number of frames each holds.
+    ///
+    /// # Panics
+    ///
+    /// If `interval` is not greater than zero, this function panics.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader and writer errors.
+    pub fn split_by_time(
+        &mut self,
+        interval: f32,
+        out_pattern: impl Fn(usize) -> PathBuf,
+    ) -> Result<Vec<(PathBuf, usize)>, Error> {
+        assert!(
+            interval > 0.0,
+            "cannot split at time boundaries an interval of {interval} apart"
+        );
+
+        let mut chunks: Vec<(PathBuf, usize)> = Vec::new();
+        let mut out = None;
+        let mut frame = Frame::default();
+        let mut origin = None;
+        let mut next_split = f32::NEG_INFINITY;
+        while self.read_frame_into(&mut frame)? {
+            let origin = *origin.get_or_insert(frame.time);
+            if frame.time >= next_split {
+                // This frame crosses into a later window: start the chunk it belongs to, and
+                // note where the window after that one begins.
+                let window = ((frame.time - origin) / interval).floor();
+                next_split = origin + (window + 1.0) * interval;
+                let path = out_pattern(chunks.len());
+                out = Some(XTCWriter::create(&path)?);
+                chunks.push((path, 0));
+            }
+            out.as_mut().unwrap().write_frame(&frame)?;
+            chunks.last_mut().unwrap().1 += 1;
+        }
+        Ok(chunks)
     }
 
     /// Reads and returns a [`Frame`] according to the [`AtomSelection`], and advances one step.
@@ -296,7 +1522,7 @@ impl<R: Read> XTCReader<R> {
         &mut self,
         frame: &mut Frame,
         atom_selection: &AtomSelection,
-    ) -> io::Result<()> {
+    ) -> Result<(), Error> {
         // Take the thread-local SCRATCH and use that while decoding the values.
         let mut scratch = SCRATCH.take();
         self.read_frame_with_scratch(frame, &mut scratch, atom_selection)
@@ -320,7 +1546,7 @@ impl<R: Read> XTCReader<R> {
         frame: &mut Frame,
         scratch: &mut Vec<u8>,
         atom_selection: &AtomSelection,
-    ) -> io::Result<()> {
+    ) -> Result<(), Error> {
         self.read_frame_with_scratch_impl::<UnBuffered>(frame, scratch, atom_selection)
     }
 
@@ -330,13 +1556,38 @@ impl<R: Read> XTCReader<R> {
         frame: &mut Frame,
         scratch: &'s mut Vec<u8>,
         atom_selection: &AtomSelection,
-    ) -> io::Result<()> {
+    ) -> Result<(), Error> {
+        let decode_start = Instant::now();
+
         // Start of by reading the header.
         let header = self.read_header()?;
 
+        if self.unwrap_step {
+            if let Some((last_step, last_time)) = self.last_raw_step {
+                if header.step < last_step && header.time >= last_time {
+                    self.step_wraps += 1;
+                }
+            }
+            self.last_raw_step = Some((header.step, header.time));
+        }
+
+        if self.expect_constant_natoms {
+            match self.expected_natoms {
+                None => self.expected_natoms = Some(header.natoms),
+                Some(expected) if expected != header.natoms => {
+                    return Err(Error::UnexpectedNatoms {
+                        expected,
+                        found: header.natoms,
+                        frame: Some(self.step),
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+
         // Now, we read the atoms.
-        if header.natoms <= 9 {
-            self.read_smol_positions(header.natoms, frame, atom_selection)?;
+        let nbytes_positions = if header.natoms <= 9 {
+            self.read_smol_positions(header.natoms, frame, atom_selection)?
         } else {
             read_positions::<B, R>(
                 &mut self.file,
@@ -345,153 +1596,735 @@ impl<R: Read> XTCReader<R> {
                 frame,
                 atom_selection,
                 header.magic,
-            )?;
-        }
+            )?
+        };
 
         self.step += 1;
+        self.bytes_read += (Header::SIZE + nbytes_positions) as u64;
+        self.frame_stats = Some(FrameStats {
+            compressed_size: nbytes_positions,
+            total_size: Header::SIZE + nbytes_positions,
+        });
 
         frame.step = header.step;
         frame.time = header.time;
         frame.boxvec = header.boxvec;
 
+        // Convert the decoded coordinates into the configured units. The stored precision is
+        // deliberately left in file units.
+        let factor = self.units.factor();
+        if factor != 1.0 {
+            for value in &mut frame.positions {
+                *value *= factor;
+            }
+            frame.boxvec *= factor;
+        }
+
+        // Stop the decode clock before handing the frame to any registered transform.
+        self.read_stats.frames += 1;
+        self.read_stats.selected_atoms += (frame.positions.len() / 3) as u64;
+        self.read_stats.compressed_bytes += nbytes_positions as u64;
+        self.read_stats.decode_time += decode_start.elapsed();
+
+        if let Some(transform) = &mut self.transform {
+            transform(frame);
+        }
+
+        if self.check_finite {
+            if let Some(idx) = frame.positions.iter().position(|value| !value.is_finite()) {
+                return Err(Error::NonFiniteCoords {
+                    frame: self.step - 1,
+                    atom: idx / 3,
+                });
+            }
+        }
+
+        if let Some(callback) = &mut self.on_progress {
+            callback(Progress {
+                bytes_read: self.bytes_read,
+                file_len: self.file_len,
+                frames_read: self.step,
+            });
+        }
+
         Ok(())
     }
 }
 
-impl XTCReader<File> {
-    /// Reset the reader to its initial position.
+/// An iterator over the frames of a trajectory, created by [`XTCReader::frames`].
+///
+/// Yields `Result<Frame, Error>`, such that decode errors are propagated to the caller rather than
+/// silently terminating the iteration. The iterator stops cleanly once the end of the trajectory
+/// is reached.
+pub struct Frames<'r, R> {
+    reader: &'r mut XTCReader<R>,
+    atom_selection: AtomSelection,
+    done: bool,
+}
+
+impl<R: Read> Iterator for Frames<'_, R> {
+    type Item = Result<Frame, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut frame = Frame::default();
+        match self
+            .reader
+            .read_frame_with_selection(&mut frame, &self.atom_selection)
+        {
+            Ok(()) => Some(Ok(frame)),
+            // We have found the end of the file. No more frames, we're done.
+            Err(Error::Truncated) => {
+                self.done = true;
+                None
+            }
+            // Something else went wrong...
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// An iterator over the frames matched by a [`Query`], created by [`XTCReader::read`].
+///
+/// Yields `Result<Frame, Error>`, such that decode errors are propagated to the caller rather
+/// than silently terminating the iteration. The iterator stops cleanly once the end of the
+/// trajectory or the end of the frame selection is reached.
+pub struct QueryFrames<'r, R> {
+    reader: &'r mut XTCReader<R>,
+    query: Query,
+    /// The index of the next frame, counted from the position the reader started at.
+    idx: usize,
+    done: bool,
+}
+
+impl<R: Read> Iterator for QueryFrames<'_, R> {
+    type Item = Result<Frame, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let included = match self.query.frames.is_included(self.idx) {
+                Some(included) => included,
+                // The selection holds no frames beyond this point.
+                None => break,
+            };
+            self.idx += 1;
+
+            if !included {
+                match self.reader.skip_frame() {
+                    Ok(true) => continue,
+                    Ok(false) => break,
+                    Err(err) => {
+                        self.done = true;
+                        return Some(Err(err));
+                    }
+                }
+            }
+
+            let mut frame = Frame::default();
+            return match self
+                .reader
+                .read_frame_into_with_selection(&mut frame, &self.query.atoms)
+            {
+                Ok(true) => Some(Ok(frame)),
+                // We have found the end of the file. No more frames, we're done.
+                Ok(false) => break,
+                Err(err) => {
+                    self.done = true;
+                    Some(Err(err))
+                }
+            };
+        }
+        self.done = true;
+        None
+    }
+}
+
+impl<R: Read + Seek> XTCReader<R> {
+    /// Set the default [`AtomSelection`] applied by the plain reading functions, validating it
+    /// against the number of atoms in the trajectory.
     ///
-    /// Go back to the first frame.
-    pub fn home(&mut self) -> io::Result<()> {
-        self.file.seek(SeekFrom::Start(0))?;
-        self.step = 0;
+    /// The number of atoms is taken from the header of the next frame, which is scanned without
+    /// moving the reader. A selection built for a larger system—say, a mask over 1200 atoms
+    /// against a 1000-atom trajectory—silently produces undefined trailing positions when read;
+    /// see [`AtomSelection::validate`]. This function rejects such a selection up front.
+    ///
+    /// # Errors
+    ///
+    /// If the selection does not fit the trajectory, an error carrying the offending sizes is
+    /// returned and the previously set selection is kept. Reader errors are passed through.
+    pub fn set_atom_selection(&mut self, atom_selection: AtomSelection) -> io::Result<()> {
+        let pos = self.file.stream_position()?;
+        let step = self.step;
+        let header = self.scan_header()?;
+        self.file.seek(SeekFrom::Start(pos))?;
+        self.step = step;
+
+        if let Some(header) = header {
+            atom_selection
+                .validate(header.natoms)
+                .map_err(io::Error::other)?;
+        }
+        self.atom_selection = atom_selection;
         Ok(())
     }
 
-    /// Returns the offsets from the headers in this [`XTCReader<R>`] from its current position.
-    ///
-    /// The last value points one byte after the last byte in the reader.
+    /// Read the metadata of the frame at the current position and skip to the next frame.
     ///
-    /// If this function is called when the internal reader is not at its starting position, the
-    /// frame offsets _from_ its position are determined. If you wish to determine the offsets from
-    /// the initial reader position, call [`XTCReader::home`] before calling this function.
+    /// The coordinate block is skipped over using its stored byte count, rather than decompressed,
+    /// which makes scanning a trajectory for steps and times an order of magnitude faster than
+    /// reading its frames. Returns [`None`] once the end of the trajectory is reached.
     ///
     /// # Errors
     ///
     /// This function will pass through any reader errors.
-    pub fn determine_offsets_exclusive(&mut self, until: Option<usize>) -> io::Result<Box<[u64]>> {
-        let file = &mut self.file;
-        // Remember where we start so we can return to it later.
-        let start_pos = file.stream_position()?;
+    pub fn scan_header(&mut self) -> Result<Option<FrameHeader>, Error> {
+        let header = match self.read_header() {
+            Ok(header) => header,
+            Err(Error::Truncated) => return Ok(None),
+            Err(err) => return Err(err),
+        };
 
-        let mut offsets = Vec::new();
+        let precision = if header.natoms <= 9 {
+            // The positions are uncompressed, and there is no precision field to speak of.
+            self.file
+                .seek(SeekFrom::Current(header.natoms as i64 * 3 * 4))?;
+            None
+        } else {
+            let precision = read_f32(&mut self.file)?;
+            // Skip over the remainder of the prelude (minint, maxint, smallidx).
+            self.file
+                .seek(SeekFrom::Current((NBYTES_POSITIONS_PRELUDE) as i64))?;
+            // The size of the buffer is stored either as a 64 or 32-bit integer, depending on the
+            // magic number in the header.
+            let nbytes = read_nbytes(&mut self.file, header.magic)?;
+            self.file
+                .seek(SeekFrom::Current((nbytes + padding(nbytes)) as i64))?;
+            Some(precision)
+        };
 
-        while until.map_or(true, |until| offsets.len() < until) {
-            let header = match Header::read(file) {
-                Ok(header) => header,
-                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
-                Err(err) => Err(err)?,
-            };
+        self.step += 1;
 
-            let skip = if header.natoms <= 9 {
-                // Know how many bytes are in this frame until the next header since the positions
-                // are uncompressed.
-                header.natoms as u64 * 3 * 4
-            } else {
-                // We need to read the nbytes value to get the offset until the next header.
-                file.seek(SeekFrom::Current(32))?;
-                // The size of the buffer is stored either as a 64 or 32-bit integer, depending on
-                // the magic number in the header.
-                let nbytes = read_nbytes(file, header.magic)? as u64;
-                nbytes + padding(nbytes as usize) as u64
+        Ok(Some(FrameHeader {
+            natoms: header.natoms,
+            step: header.step,
+            time: header.time,
+            boxvec: header.boxvec,
+            precision,
+        }))
+    }
+
+    /// Read the metadata of the next frame without advancing past it.
+    ///
+    /// A merge or interleave across several readers wants to compare the upcoming times and
+    /// consume only the earliest, which needs look-ahead. The header is scanned and the reader
+    /// is rewound to the start of the frame, so the next read still yields the peeked frame. The
+    /// header is cached against the position it was read at, which makes repeated peeks free;
+    /// any movement of the reader—a read or a seek—leaves that position behind and thereby
+    /// invalidates the cache. Returns [`None`] once the end of the trajectory is reached.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors.
+    pub fn peek_header(&mut self) -> io::Result<Option<&FrameHeader>> {
+        let pos = self.file.stream_position()?;
+        if self.peeked.map_or(true, |(at, _)| at != pos) {
+            let step = self.step;
+            let header = self.scan_header()?;
+            self.file.seek(SeekFrom::Start(pos))?;
+            self.step = step;
+            self.peeked = match header {
+                Some(header) => Some((pos, header)),
+                None => return Ok(None),
             };
-            let offset = file.seek(SeekFrom::Current(skip as i64))?;
-            offsets.push(offset);
         }
-
-        // Return back to where we started.
-        file.seek(SeekFrom::Start(start_pos))?;
-
-        Ok(offsets.into_boxed_slice())
+        Ok(self.peeked.as_ref().map(|(_, header)| header))
     }
 
-    /// Returns the offsets of this [`XTCReader<R>`] from its current position.
+    /// Position the reader at the first frame whose time is greater than or equal to `time`.
     ///
-    /// The last value points to the start of the last frame.
+    /// The trajectory is scanned from the start, reading only the frame headers. If an
+    /// [`XTCIndex`] is available, [`XTCReader::seek_to_time_indexed`] avoids the scan altogether.
     ///
-    /// If this function is called when the internal reader is not at its starting position, the
-    /// frame offsets _from_ its position are determined. If you wish to determine the offsets from
-    /// the initial reader position, call [`XTCReader::home`] before calling this function.
+    /// Returns the index of the frame that was landed on, such that the caller can confirm where
+    /// the reader ended up. If `time` lies before the first frame, the reader is positioned at
+    /// frame 0. If `time` lies beyond the last frame, [`None`] is returned.
     ///
     /// # Errors
     ///
     /// This function will pass through any reader errors.
-    pub fn determine_offsets(&mut self, until: Option<usize>) -> io::Result<Box<[u64]>> {
-        let mut offsets = vec![0];
-        let exclusive = self.determine_offsets_exclusive(until)?;
-        offsets.extend(exclusive.iter().take(exclusive.len().saturating_sub(1)));
-        Ok(offsets.into_boxed_slice())
+    pub fn seek_to_time(&mut self, time: f32) -> Result<Option<usize>, Error> {
+        self.home()?;
+        let mut idx = 0;
+        loop {
+            let offset = self.file.stream_position()?;
+            match self.scan_header()? {
+                Some(header) if header.time >= time => {
+                    self.file.seek(SeekFrom::Start(offset))?;
+                    self.step = idx;
+                    return Ok(Some(idx));
+                }
+                Some(_) => idx += 1,
+                None => return Ok(None),
+            }
+        }
     }
 
-    /// Returns the frame sizes of this [`XTCReader<R>`].
+    /// Detect the time interval between consecutive frames.
+    ///
+    /// GROMACS writes frames a fixed number of integration steps apart, and the resulting time
+    /// between frames is a parameter many analyses need up front. The interval is taken from the
+    /// times of the first two frame headers; the coordinates are not decoded. Returns [`None`]
+    /// if the trajectory holds fewer than two frames. The reader is rewound to the position it
+    /// started at.
+    ///
+    /// # Note
+    ///
+    /// Only the first two frames are inspected, so a non-uniform save interval goes undetected.
+    /// [`XTCReader::detect_timestep_checked`] scans the whole trajectory instead.
     ///
     /// # Errors
     ///
     /// This function will pass through any reader errors.
-    pub fn determine_frame_sizes(&mut self, until: Option<usize>) -> io::Result<Box<[u64]>> {
-        let starts = self.determine_offsets_exclusive(until)?;
-        let ends = starts.iter().clone().skip(1);
-        Ok(starts
-            .iter()
-            .zip(ends)
-            .map(|(s, e)| e - s)
-            .collect::<Vec<_>>()
-            .into_boxed_slice())
+    pub fn detect_timestep(&mut self) -> io::Result<Option<f32>> {
+        let pos = self.file.stream_position()?;
+        let step = self.step;
+        self.home()?;
+
+        let first = self.scan_header()?;
+        let second = self.scan_header()?;
+
+        self.file.seek(SeekFrom::Start(pos))?;
+        self.step = step;
+        match (first, second) {
+            (Some(first), Some(second)) => Ok(Some(second.time - first.time)),
+            _ => Ok(None),
+        }
     }
 
-    /// Seeks to offset, then reads and returns a [`Frame`] and advances one step.
+    /// Detect the time interval between consecutive frames, verifying that it is uniform.
     ///
-    /// # Note
+    /// Where [`XTCReader::detect_timestep`] trusts the first two frames, this scans the headers
+    /// of the whole trajectory and checks that every consecutive pair of frames lies the same
+    /// time apart, up to `tolerance`. Returns [`None`] if the trajectory holds fewer than two
+    /// frames. The reader is rewound to the position it started at.
     ///
-    /// The `BUFFERED` const generic value can be used to set whether the frame reader will read in
-    /// a buffered manner or not at compile time.
+    /// # Errors
     ///
-    /// Buffered reading is most favorable when a small number of positions are read from the top of
-    /// the frame (leaving many positions that do not need to be read at the bottom), especially at the
-    /// point where disk read speed is a bottleneck.
-    pub fn read_frame_at_offset<const BUFFERED: bool>(
-        &mut self,
-        frame: &mut Frame,
-        offset: u64,
-        atom_selection: &AtomSelection,
-    ) -> io::Result<()> {
-        self.file.seek(SeekFrom::Start(offset))?;
-        match BUFFERED {
-            false => self.read_frame_with_selection(frame, atom_selection),
-            true => self.read_frame_with_selection_buffered(frame, atom_selection),
+    /// If any interval deviates from the first by more than `tolerance`, an error reporting the
+    /// offending frames is returned. Reader errors are passed through.
+    pub fn detect_timestep_checked(&mut self, tolerance: f32) -> io::Result<Option<f32>> {
+        let pos = self.file.stream_position()?;
+        let step = self.step;
+        self.home()?;
+
+        let mut times = Vec::new();
+        while let Some(header) = self.scan_header()? {
+            times.push(header.time);
+        }
+
+        self.file.seek(SeekFrom::Start(pos))?;
+        self.step = step;
+
+        let timestep = match times.windows(2).next() {
+            Some(pair) => pair[1] - pair[0],
+            None => return Ok(None),
+        };
+        for (idx, pair) in times.windows(2).enumerate().skip(1) {
+            let interval = pair[1] - pair[0];
+            if (interval - timestep).abs() > tolerance {
+                return Err(io::Error::other(format!(
+                    "the interval between frames {idx} and {} is {interval}, where the frames \
+                    before lie {timestep} apart",
+                    idx + 1
+                )));
+            }
         }
+        Ok(Some(timestep))
     }
 
-    /// Append [`Frame`]s to the `frames` buffer according to a [`Selection`].
+    /// Select frames such that consecutive selected frames lie at least `interval` apart in
+    /// time.
     ///
-    /// If successful, it will return the number of frames that were read.
-    /// This can be useful since the selection itself is not enough to tell how many frames will
-    /// actually be read.
+    /// The trajectory is scanned from the start, reading only the frame headers. The first frame
+    /// is always selected, and after that, the first frame whose time is at least `interval`
+    /// past the last selected frame's time is. Contrary to downsampling by a fixed integer step,
+    /// this tolerates trajectories with a non-uniform save interval.
     ///
-    /// # Note
+    /// The returned [`FrameSelection`] holds the chosen frame indices, such that the
+    /// downsampling is reproducible and can be applied through the regular reading functions.
+    /// The reader is rewound to the position it started at.
     ///
-    /// The `BUFFERED` const generic value can be used to set whether the frame reader will read in
-    /// a buffered manner or not at compile time.
+    /// # Errors
     ///
-    /// Buffered reading is most favorable when a small number of positions are read from the top of
-    /// the frame (leaving many positions that do not need to be read at the bottom), especially at the
-    /// point where disk read speed is a bottleneck.
-    pub fn read_frames<const BUFFERED: bool>(
-        &mut self,
-        frames: &mut impl Extend<Frame>,
-        frame_selection: &FrameSelection,
+    /// This function will pass through any reader errors.
+    pub fn select_by_time_interval(&mut self, interval: f32) -> Result<FrameSelection, Error> {
+        let pos = self.file.stream_position()?;
+        let step = self.step;
+        self.home()?;
+
+        let mut indices = Vec::new();
+        let mut last_time = f32::NEG_INFINITY;
+        let mut idx = 0;
+        while let Some(header) = self.scan_header()? {
+            if header.time - last_time >= interval {
+                indices.push(idx);
+                last_time = header.time;
+            }
+            idx += 1;
+        }
+
+        self.file.seek(SeekFrom::Start(pos))?;
+        self.step = step;
+        Ok(FrameSelection::framelist_from_iter(indices))
+    }
+
+    /// Select the frames whose header matches a predicate.
+    ///
+    /// The trajectory is scanned from the start, reading only the frame headers; the coordinate
+    /// blocks are skipped over without decoding. This builds selections that are defined by a
+    /// property—frames where `header.step % 5000 == 0`, or within a time window, say—in a single
+    /// cheap pass over the metadata. The indices are collected in scan order, which upholds the
+    /// uniqueness and ordering invariants of the returned
+    /// [`FrameList`](FrameSelection::FrameList).
+    ///
+    /// The returned selection can be fed back into the regular reading functions, or to the
+    /// parallel reader. The reader is rewound to the position it started at.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors.
+    pub fn select_frames_where(
+        &mut self,
+        mut pred: impl FnMut(&FrameHeader) -> bool,
+    ) -> Result<FrameSelection, Error> {
+        let pos = self.file.stream_position()?;
+        let step = self.step;
+        self.home()?;
+
+        let mut indices = Vec::new();
+        let mut idx = 0;
+        while let Some(header) = self.scan_header()? {
+            if pred(&header) {
+                indices.push(idx);
+            }
+            idx += 1;
+        }
+
+        self.file.seek(SeekFrom::Start(pos))?;
+        self.step = step;
+        Ok(FrameSelection::framelist_from_iter(indices))
+    }
+
+    /// Read the next frame into `frame`, skipping over corrupt or truncated data.
+    ///
+    /// Where [`XTCReader::read_frame`] returns an error on a malformed frame—losing access to any
+    /// frames that follow it—this function scans forward for the next valid frame header and
+    /// reports the damage as [`FrameReadOutcome::Skipped`], such that the remainder of a partially
+    /// written trajectory can still be recovered:
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use molly::FrameReadOutcome;
+    ///
+    /// let mut reader = molly::XTCReader::open("truncated.xtc")?;
+    /// let mut frame = molly::Frame::default();
+    /// loop {
+    ///     match reader.read_frame_resilient(&mut frame)? {
+    ///         FrameReadOutcome::Read => { /* Do something with the frame. */ }
+    ///         FrameReadOutcome::Skipped { bytes } => eprintln!("skipped {bytes} corrupt bytes"),
+    ///         FrameReadOutcome::Eof => break,
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// When a frame fails to read, the bytes after its start are searched for an xtc magic number,
+    /// and each candidate is checked to be the start of a plausible header. If one is found, the
+    /// reader is positioned there and [`FrameReadOutcome::Skipped`] is returned. If the rest of
+    /// the file holds no valid header—as for a trajectory that was cut off mid-write—the outcome
+    /// is [`FrameReadOutcome::Eof`].
+    ///
+    /// # Errors
+    ///
+    /// Seek and read errors from the underlying file are passed through. Decode errors do not
+    /// surface as errors here; they are what this function exists to skip over.
+    pub fn read_frame_resilient(&mut self, frame: &mut Frame) -> Result<FrameReadOutcome, Error> {
+        self.read_frame_resilient_with_selection(frame, &AtomSelection::All)
+    }
+
+    /// Read the next frame according to an [`AtomSelection`], skipping over corrupt or truncated
+    /// data.
+    ///
+    /// See [`XTCReader::read_frame_resilient`].
+    pub fn read_frame_resilient_with_selection(
+        &mut self,
+        frame: &mut Frame,
         atom_selection: &AtomSelection,
-    ) -> io::Result<usize> {
+    ) -> Result<FrameReadOutcome, Error> {
+        let start = self.file.stream_position()?;
+        match self.read_frame_with_selection(frame, atom_selection) {
+            Ok(()) => return Ok(FrameReadOutcome::Read),
+            // Any failure to decode the frame at `start`—a bad magic number, a nonsense header, or
+            // data that ends halfway through—sends us scanning for the next frame below.
+            Err(_) => self.file.seek(SeekFrom::Start(start))?,
+        };
+
+        match self.scan_for_header(start + 1)? {
+            Some(offset) => {
+                self.file.seek(SeekFrom::Start(offset))?;
+                Ok(FrameReadOutcome::Skipped {
+                    bytes: offset - start,
+                })
+            }
+            None => Ok(FrameReadOutcome::Eof),
+        }
+    }
+
+    /// Scan forward from `from` for the first offset that holds a plausible frame header.
+    ///
+    /// Candidate offsets are found by searching for the big-endian magic numbers, and verified by
+    /// reading a full [`Header`] at that position. Returns [`None`] if the rest of the file holds
+    /// no valid header. The position of the reader after this function is unspecified.
+    fn scan_for_header(&mut self, from: u64) -> Result<Option<u64>, Error> {
+        const MAGICS: [[u8; 4]; 2] = [Magic::XTC_1995.to_be_bytes(), Magic::XTC_2023.to_be_bytes()];
+        let mut buf = [0u8; 8192];
+        let mut offset = self.file.seek(SeekFrom::Start(from))?;
+
+        loop {
+            let n = {
+                // Fill as much of the buffer as we can, since we may get short reads.
+                let mut filled = 0;
+                while filled < buf.len() {
+                    match self.file.read(&mut buf[filled..])? {
+                        0 => break,
+                        n => filled += n,
+                    }
+                }
+                filled
+            };
+            if n < MAGICS[0].len() {
+                return Ok(None);
+            }
+
+            for idx in 0..=n - MAGICS[0].len() {
+                let window: [u8; 4] = buf[idx..idx + 4].try_into().unwrap();
+                if !MAGICS.contains(&window) {
+                    continue;
+                }
+                // Verify that this candidate is the start of a well-formed header, not just four
+                // bytes that happen to look like a magic number.
+                let candidate = offset + idx as u64;
+                self.file.seek(SeekFrom::Start(candidate))?;
+                if Header::read(&mut self.file).is_ok() {
+                    return Ok(Some(candidate));
+                }
+                self.file.seek(SeekFrom::Start(offset + n as u64))?;
+            }
+
+            // Overlap the windows so a magic number straddling two buffers is not missed.
+            offset += (n - (MAGICS[0].len() - 1)) as u64;
+            self.file.seek(SeekFrom::Start(offset))?;
+        }
+    }
+
+    /// Reset the reader to its initial position.
+    ///
+    /// Go back to the first frame.
+    pub fn home(&mut self) -> Result<(), Error> {
+        self.file.seek(SeekFrom::Start(0))?;
+        self.step = 0;
+        self.bytes_read = 0;
+        self.last_raw_step = None;
+        self.step_wraps = 0;
+        self.expected_natoms = None;
+        self.frame_stats = None;
+        self.read_stats = ReadStats::default();
+        Ok(())
+    }
+
+    /// Count the frames in this trajectory without decoding any coordinates.
+    ///
+    /// The whole file is walked reading only the frame headers; the compressed blocks are skipped
+    /// over by their stored byte size, which makes counting orders of magnitude cheaper than
+    /// reading. The reader is returned to the position it was at before this function was called.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors.
+    pub fn count_frames(&mut self) -> Result<usize, Error> {
+        // Remember where we are so we can return to it later.
+        let start_pos = self.file.stream_position()?;
+        let start_step = self.step;
+
+        self.file.seek(SeekFrom::Start(0))?;
+        let mut count = 0;
+        while self.skip_frame()? {
+            count += 1;
+        }
+
+        self.file.seek(SeekFrom::Start(start_pos))?;
+        self.step = start_step;
+        Ok(count)
+    }
+
+    /// Count the frames that `frame_selection` would yield from this trajectory.
+    ///
+    /// Like [`XTCReader::count_frames`], no coordinates are decoded, and the reader is returned
+    /// to the position it was at before this function was called.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors.
+    pub fn count_selected_frames(
+        &mut self,
+        frame_selection: &FrameSelection,
+    ) -> Result<usize, Error> {
+        let nframes = self.count_frames()?;
+        let mut count = 0;
+        for idx in 0..nframes {
+            match frame_selection.is_included(idx) {
+                Some(true) => count += 1,
+                Some(false) => {}
+                // The selection holds no frames beyond this point.
+                None => break,
+            }
+        }
+        Ok(count)
+    }
+
+    /// Returns the offsets from the headers in this [`XTCReader<R>`] from its current position.
+    ///
+    /// The last value points one byte after the last byte in the reader.
+    ///
+    /// If this function is called when the internal reader is not at its starting position, the
+    /// frame offsets _from_ its position are determined. If you wish to determine the offsets from
+    /// the initial reader position, call [`XTCReader::home`] before calling this function.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors.
+    pub fn determine_offsets_exclusive(
+        &mut self,
+        until: Option<usize>,
+    ) -> Result<Box<[u64]>, Error> {
+        let file = &mut self.file;
+        // Remember where we start so we can return to it later.
+        let start_pos = file.stream_position()?;
+
+        let mut offsets = Vec::new();
+
+        while until.map_or(true, |until| offsets.len() < until) {
+            let header = match Header::read(file) {
+                Ok(header) => header,
+                Err(Error::Truncated) => break,
+                Err(err) => Err(err)?,
+            };
+
+            let skip = if header.natoms <= 9 {
+                // Know how many bytes are in this frame until the next header since the positions
+                // are uncompressed.
+                header.natoms as u64 * 3 * 4
+            } else {
+                // We need to read the nbytes value to get the offset until the next header.
+                file.seek(SeekFrom::Current(32))?;
+                // The size of the buffer is stored either as a 64 or 32-bit integer, depending on
+                // the magic number in the header.
+                let nbytes = read_nbytes(file, header.magic)? as u64;
+                nbytes + padding(nbytes as usize) as u64
+            };
+            let offset = file.seek(SeekFrom::Current(skip as i64))?;
+            offsets.push(offset);
+        }
+
+        // Return back to where we started.
+        file.seek(SeekFrom::Start(start_pos))?;
+
+        Ok(offsets.into_boxed_slice())
+    }
+
+    /// Returns the offsets of this [`XTCReader<R>`] from its current position.
+    ///
+    /// The last value points to the start of the last frame.
+    ///
+    /// If this function is called when the internal reader is not at its starting position, the
+    /// frame offsets _from_ its position are determined. If you wish to determine the offsets from
+    /// the initial reader position, call [`XTCReader::home`] before calling this function.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors.
+    pub fn determine_offsets(&mut self, until: Option<usize>) -> Result<Box<[u64]>, Error> {
+        let mut offsets = vec![0];
+        let exclusive = self.determine_offsets_exclusive(until)?;
+        offsets.extend(exclusive.iter().take(exclusive.len().saturating_sub(1)));
+        Ok(offsets.into_boxed_slice())
+    }
+
+    /// Returns the frame sizes of this [`XTCReader<R>`].
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors.
+    pub fn determine_frame_sizes(&mut self, until: Option<usize>) -> Result<Box<[u64]>, Error> {
+        let starts = self.determine_offsets_exclusive(until)?;
+        let ends = starts.iter().clone().skip(1);
+        Ok(starts
+            .iter()
+            .zip(ends)
+            .map(|(s, e)| e - s)
+            .collect::<Vec<_>>()
+            .into_boxed_slice())
+    }
+
+    /// Seeks to offset, then reads and returns a [`Frame`] and advances one step.
+    ///
+    /// # Note
+    ///
+    /// The `BUFFERED` const generic value can be used to set whether the frame reader will read in
+    /// a buffered manner or not at compile time.
+    ///
+    /// Buffered reading is most favorable when a small number of positions are read from the top of
+    /// the frame (leaving many positions that do not need to be read at the bottom), especially at the
+    /// point where disk read speed is a bottleneck.
+    pub fn read_frame_at_offset<const BUFFERED: bool>(
+        &mut self,
+        frame: &mut Frame,
+        offset: u64,
+        atom_selection: &AtomSelection,
+    ) -> Result<(), Error> {
+        self.file.seek(SeekFrom::Start(offset))?;
+        match BUFFERED {
+            false => self.read_frame_with_selection(frame, atom_selection),
+            true => self.read_frame_with_selection_buffered(frame, atom_selection),
+        }
+    }
+
+    /// Append [`Frame`]s to the `frames` buffer according to a [`Selection`].
+    ///
+    /// If successful, it will return the number of frames that were read.
+    /// This can be useful since the selection itself is not enough to tell how many frames will
+    /// actually be read.
+    ///
+    /// # Note
+    ///
+    /// The `BUFFERED` const generic value can be used to set whether the frame reader will read in
+    /// a buffered manner or not at compile time.
+    ///
+    /// Buffered reading is most favorable when a small number of positions are read from the top of
+    /// the frame (leaving many positions that do not need to be read at the bottom), especially at the
+    /// point where disk read speed is a bottleneck.
+    pub fn read_frames<const BUFFERED: bool>(
+        &mut self,
+        frames: &mut impl Extend<Frame>,
+        frame_selection: &FrameSelection,
+        atom_selection: &AtomSelection,
+    ) -> Result<usize, Error> {
         let offsets = self.determine_offsets(frame_selection.until())?;
         let mut n = 0;
         for (idx, &offset) in offsets.iter().enumerate() {
@@ -509,12 +2342,84 @@ impl XTCReader<File> {
         Ok(n)
     }
 
+    /// Read the selected frames and atoms into a dense [`CoordinateArray`].
+    ///
+    /// The selected frames are counted up front, and the number of selected atoms follows from
+    /// the first selected frame, such that the array is allocated exactly once. The whole
+    /// trajectory is walked from the start, and the reader is returned to the position it was at
+    /// before this function was called.
+    ///
+    /// # Errors
+    ///
+    /// If the number of selected atoms differs between frames, an error is returned. Reader
+    /// errors are passed through.
+    pub fn read_to_array(
+        &mut self,
+        frame_selection: &FrameSelection,
+        atom_selection: &AtomSelection,
+    ) -> Result<CoordinateArray, Error> {
+        let nframes = self.count_selected_frames(frame_selection)?;
+
+        // Remember where we are so we can return to it later.
+        let start_pos = self.file.stream_position()?;
+        let start_step = self.step;
+        self.file.seek(SeekFrom::Start(0))?;
+
+        let mut data = Vec::new();
+        let mut natoms = None;
+        let mut frame = Frame::default();
+        let mut idx = 0;
+        loop {
+            match frame_selection.is_included(idx) {
+                Some(true) => {
+                    if !self.read_frame_into_with_selection(&mut frame, atom_selection)? {
+                        break;
+                    }
+                    let selected = frame.natoms();
+                    match natoms {
+                        None => {
+                            natoms = Some(selected);
+                            data.reserve_exact(nframes * selected * 3);
+                        }
+                        Some(natoms) if natoms != selected => {
+                            return Err(io::Error::other(format!(
+                                "frame {idx} selects {selected} atoms, where the frames before \
+                                it selected {natoms}"
+                            ))
+                            .into());
+                        }
+                        Some(_) => {}
+                    }
+                    data.extend_from_slice(&frame.positions);
+                }
+                Some(false) => {
+                    if !self.skip_frame()? {
+                        break;
+                    }
+                }
+                // The selection holds no frames beyond this point.
+                None => break,
+            }
+            idx += 1;
+        }
+
+        // Return back to where we started.
+        self.file.seek(SeekFrom::Start(start_pos))?;
+        self.step = start_step;
+
+        Ok(CoordinateArray {
+            data,
+            nframes,
+            natoms: natoms.unwrap_or(0),
+        })
+    }
+
     /// Reads and returns a [`Frame`] according to the [`AtomSelection`], and advances one step.
     pub fn read_frame_with_selection_buffered(
         &mut self,
         frame: &mut Frame,
         atom_selection: &AtomSelection,
-    ) -> io::Result<()> {
+    ) -> Result<(), Error> {
         // Take the thread-local SCRATCH and use that while decoding the values.
         let mut scratch = SCRATCH.take();
         self.read_frame_with_scratch_buffered(frame, &mut scratch, atom_selection)
@@ -538,7 +2443,1233 @@ impl XTCReader<File> {
         frame: &mut Frame,
         scratch: &mut Vec<u8>,
         atom_selection: &AtomSelection,
-    ) -> io::Result<()> {
-        self.read_frame_with_scratch_impl::<Buffer>(frame, scratch, atom_selection)
+    ) -> Result<(), Error> {
+        self.read_frame_with_scratch_impl::<Buffer<R>>(frame, scratch, atom_selection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{test_frame, write_trajectory, TempPath};
+    use crate::writer::XTCWriter;
+
+    #[test]
+    fn scan_headers() -> io::Result<()> {
+        // A final tiny frame, which takes the uncompressed layout.
+        let tiny = Frame {
+            step: 40,
+            time: 8.0,
+            positions: vec![0.0; 3 * 4],
+            ..Frame::default()
+        };
+        let path = write_trajectory(
+            "scan_headers",
+            (0..4)
+                .map(|step| test_frame(step * 10, step as f32 * 2.0, 100))
+                .chain([tiny]),
+        )?;
+
+        let mut reader = XTCReader::open(&path)?;
+        let mut headers = Vec::new();
+        while let Some(header) = reader.scan_header()? {
+            headers.push(header);
+        }
+
+        assert_eq!(headers.len(), 5);
+        assert_eq!(reader.step, 5);
+        for (idx, header) in headers.iter().enumerate() {
+            assert_eq!(header.step, idx as u32 * 10);
+            assert_eq!(header.time, idx as f32 * 2.0);
+        }
+        assert_eq!(headers[0].natoms, 100);
+        assert_eq!(headers[0].precision, Some(1000.0));
+        assert_eq!(headers[4].natoms, 4);
+        assert_eq!(headers[4].precision, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn select_by_time_interval() -> io::Result<()> {
+        // A non-uniform save interval, which a fixed integer step cannot downsample correctly.
+        let times = [0.0, 2.0, 4.0, 9.0, 10.0, 20.0, 21.0];
+        let path = write_trajectory(
+            "time_interval",
+            times
+                .iter()
+                .enumerate()
+                .map(|(step, &time)| test_frame(step as u32, time, 20)),
+        )?;
+
+        let mut reader = XTCReader::open(&path)?;
+        let selection = reader.select_by_time_interval(5.0)?;
+        // The first frame is always selected; each next frame lies at least 5 ps further.
+        assert!(matches!(&selection, FrameSelection::FrameList(list) if list.len() == 3));
+        for idx in 0..times.len() {
+            assert_eq!(
+                selection.is_included(idx).unwrap_or(false),
+                [0, 3, 5].contains(&idx)
+            );
+        }
+
+        // The selection composes with the regular reading functions, and the scan has not moved
+        // the reader.
+        let mut frames = Vec::new();
+        reader.read_frames::<false>(&mut frames, &selection, &AtomSelection::All)?;
+        let times: Vec<f32> = frames.iter().map(|frame| frame.time).collect();
+        assert_eq!(times, [0.0, 9.0, 20.0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn peek_header_does_not_advance() -> io::Result<()> {
+        let path = write_trajectory(
+            "peek",
+            (0..3).map(|step| test_frame(step, step as f32 * 2.0, 20)),
+        )?;
+
+        let mut reader = XTCReader::open(&path)?;
+        // Peeking twice reads the header once; the second call comes from the cache.
+        let peeked = *reader.peek_header()?.unwrap();
+        assert_eq!(*reader.peek_header()?.unwrap(), peeked);
+        assert_eq!(reader.step, 0);
+
+        // The subsequent read yields the peeked frame itself; nothing is skipped.
+        let mut frame = Frame::default();
+        reader.read_frame(&mut frame)?;
+        assert_eq!(frame.step, peeked.step);
+        assert_eq!(frame.time, peeked.time);
+
+        // Peeking mid-trajectory looks at the next frame, and a seek invalidates the cache.
+        assert_eq!(reader.peek_header()?.unwrap().step, 1);
+        reader.home()?;
+        assert_eq!(reader.peek_header()?.unwrap().step, 0);
+
+        // At the end of the trajectory, there is nothing left to peek at.
+        while reader.read_frame_into(&mut frame)? {}
+        assert_eq!(reader.peek_header()?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn detect_timestep() -> io::Result<()> {
+        // A uniform save interval, until a frame breaks the pattern at the very end.
+        let times = [0.0, 2.0, 4.0, 6.0, 13.0];
+        let path = write_trajectory(
+            "detect_timestep",
+            times
+                .iter()
+                .enumerate()
+                .map(|(step, &time)| test_frame(step as u32, time, 20)),
+        )?;
+
+        let mut reader = XTCReader::open(&path)?;
+        // The quick path trusts the first two frames, and does not move the reader.
+        assert_eq!(reader.detect_timestep()?, Some(2.0));
+        assert_eq!(reader.step, 0);
+        // The checked path notices the break in the pattern.
+        assert!(reader.detect_timestep_checked(1e-3).is_err());
+
+        // With the offending frame within tolerance, the interval is reported again.
+        assert_eq!(reader.detect_timestep_checked(10.0)?, Some(2.0));
+
+        // A single-frame trajectory has no interval to speak of.
+        let mut writer = XTCWriter::create(&path)?;
+        writer.write_frame(&Frame {
+            precision: 1000.0,
+            positions: vec![0.0; 3 * 20],
+            ..Frame::default()
+        })?;
+        let mut reader = XTCReader::open(&path)?;
+        assert_eq!(reader.detect_timestep()?, None);
+        assert_eq!(reader.detect_timestep_checked(1e-3)?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_frame_f64() -> io::Result<()> {
+        let wide_frame = Frame {
+            boxvec: Mat3::from_diagonal(Vec3::new(2.0, 3.0, 4.0)),
+            ..test_frame(0, 0.5, 50)
+        };
+        // A small-system frame exercises the raw float path.
+        let smol_frame = Frame {
+            step: 1,
+            positions: vec![0.125, 0.25, 0.5, 1.0, 2.0, 4.0],
+            ..Frame::default()
+        };
+        let path = write_trajectory("f64", [wide_frame, smol_frame])?;
+
+        let mut reader = XTCReader::open(&path)?;
+        let mut narrow = Frame::default();
+        reader.read_frame(&mut narrow)?;
+        reader.home()?;
+
+        let wide = reader.read_frame_f64()?.unwrap();
+        assert_eq!(wide.step, narrow.step);
+        assert_eq!(wide.boxvec, narrow.boxvec.as_dmat3());
+        assert_eq!(wide.positions.len(), narrow.positions.len());
+        for (&w, &n) in wide.positions.iter().zip(&narrow.positions) {
+            // The widened value matches the `f32` value cast up, within the decode precision...
+            assert!((w - n as f64).abs() < 1e-6);
+            // ...and lies exactly on the stored integer grid.
+            let grid = w * wide.precision as f64;
+            assert_eq!(grid, grid.round());
+        }
+
+        // The raw floats of a small-system frame widen verbatim.
+        let smol = reader.read_frame_f64()?.unwrap();
+        assert_eq!(smol.positions, [0.125, 0.25, 0.5, 1.0, 2.0, 4.0]);
+
+        assert!(reader.read_frame_f64()?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn frame_stats() -> io::Result<()> {
+        // A small-system frame at the end exercises the uncompressed path.
+        let smol_frame = Frame {
+            step: 4,
+            positions: vec![0.0; 3 * 4],
+            ..Frame::default()
+        };
+        let path = write_trajectory(
+            "frame_stats",
+            (0..4)
+                .map(|step| test_frame(step, 0.0, 50))
+                .chain([smol_frame]),
+        )?;
+
+        let mut reader = XTCReader::open(&path)?;
+        assert!(reader.frame_stats().is_none());
+
+        // The per-frame totals account for every byte of the file.
+        let mut frame = Frame::default();
+        let mut total = 0;
+        while reader.read_frame_into(&mut frame)? {
+            let stats = reader.frame_stats().unwrap();
+            assert_eq!(stats.total_size, Header::SIZE + stats.compressed_size);
+            total += stats.total_size;
+        }
+        // The last successful read was the small-system frame: its positions are stored raw.
+        assert_eq!(
+            reader.frame_stats().unwrap().compressed_size,
+            4 * 3 * std::mem::size_of::<f32>()
+        );
+        assert_eq!(total as u64, std::fs::metadata(&path)?.len());
+
+        reader.home()?;
+        assert!(reader.frame_stats().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_stats() -> io::Result<()> {
+        let natoms = 40;
+        let path = write_trajectory(
+            "read_stats",
+            (0..5).map(|step| test_frame(step, 0.0, natoms)),
+        )?;
+
+        // Count the coordinate-block bytes independently through the per-frame stats.
+        let mut reader = XTCReader::open(&path)?;
+        let mut frame = Frame::default();
+        let mut frames = 0;
+        let mut compressed_bytes = 0;
+        while reader.read_frame_into(&mut frame)? {
+            frames += 1;
+            compressed_bytes += reader.frame_stats().unwrap().compressed_size as u64;
+        }
+
+        let stats = reader.stats();
+        assert_eq!(stats.frames, frames);
+        assert_eq!(stats.compressed_bytes, compressed_bytes);
+        assert_eq!(stats.selected_atoms, (frames * natoms) as u64);
+
+        // A selection narrows the atom count; the other counters keep accumulating.
+        reader.home()?;
+        assert_eq!(reader.stats(), ReadStats::default());
+        reader.read_frame_with_selection(&mut frame, &AtomSelection::range(None, 10, None))?;
+        reader.read_frame(&mut frame)?;
+        let stats = reader.stats();
+        assert_eq!(stats.frames, 2);
+        assert_eq!(stats.selected_atoms, (10 + natoms) as u64);
+
+        Ok(())
+    }
+
+    #[test]
+    fn select_frames_where() -> io::Result<()> {
+        let path = write_trajectory(
+            "frames_where",
+            (0..8).map(|idx| test_frame(idx * 500, idx as f32 * 2.0, 20)),
+        )?;
+
+        let mut reader = XTCReader::open(&path)?;
+        // A property over the header metadata: every frame whose step is a multiple of 1000.
+        let selection = reader.select_frames_where(|header| header.step % 1000 == 0)?;
+        assert!(matches!(&selection, FrameSelection::FrameList(list) if list.len() == 4));
+
+        // The selection composes with the regular reading functions, and the scan has not moved
+        // the reader.
+        let mut frames = Vec::new();
+        reader.read_frames::<false>(&mut frames, &selection, &AtomSelection::All)?;
+        let steps: Vec<u32> = frames.iter().map(|frame| frame.step).collect();
+        assert_eq!(steps, [0, 1000, 2000, 3000]);
+
+        // A time window.
+        let window = reader.select_frames_where(|header| (4.0..10.0).contains(&header.time))?;
+        for idx in 0..8 {
+            assert_eq!(window.is_included(idx).unwrap_or(false), (2..5).contains(&idx));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_frame_into_reuses_buffer() -> io::Result<()> {
+        let path = write_trajectory(
+            "read_frame_into",
+            (0..4).map(|step| test_frame(step, 0.0, 50)),
+        )?;
+
+        let mut reader = XTCReader::open(&path)?;
+        let mut frame = Frame::default();
+        assert!(reader.read_frame_into(&mut frame)?);
+        let ptr = frame.positions.as_ptr();
+
+        let mut nframes = 1;
+        while reader.read_frame_into(&mut frame)? {
+            nframes += 1;
+            // The positions buffer must be reused, not reallocated, while the atom count is
+            // stable.
+            assert_eq!(frame.positions.as_ptr(), ptr);
+        }
+        assert_eq!(nframes, 4);
+        assert_eq!(frame.step, 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn triclinic_box_math() {
+        // A triclinic box with vectors a, b, c.
+        let a = Vec3::new(5.0, 0.0, 0.0);
+        let b = Vec3::new(2.0, 4.0, 0.0);
+        let c = Vec3::new(1.0, 1.0, 3.0);
+        let frame = Frame {
+            boxvec: Mat3::from_cols(a, b, c),
+            ..Frame::default()
+        };
+
+        assert_eq!(frame.box_matrix(), [a.to_array(), b.to_array(), c.to_array()]);
+
+        // The volume must match a · (b × c).
+        let volume = a.dot(b.cross(c));
+        assert_eq!(frame.box_volume(), volume);
+        assert_eq!(frame.box_volume(), 60.0);
+
+        let (lengths, angles) = frame.box_lengths_angles();
+        assert_eq!(lengths, [a.length(), b.length(), c.length()]);
+        // α between b and c, β between a and c, γ between a and b.
+        for (angle, (u, v)) in angles.iter().zip([(b, c), (a, c), (a, b)]) {
+            let expected = (u.dot(v) / (u.length() * v.length())).acos().to_degrees();
+            assert!((angle - expected).abs() < 1e-4);
+        }
+
+        // A frame with a zeroed box has no volume.
+        let no_box = Frame {
+            boxvec: Mat3::ZERO,
+            ..Frame::default()
+        };
+        assert_eq!(no_box.box_volume(), 0.0);
+    }
+
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn nalgebra_box_matrix() {
+        let a = Vec3::new(5.0, 0.0, 0.0);
+        let b = Vec3::new(2.0, 4.0, 0.0);
+        let c = Vec3::new(1.0, 1.0, 3.0);
+        let frame = Frame {
+            boxvec: Mat3::from_cols(a, b, c),
+            ..Frame::default()
+        };
+
+        // The matrix rows are the box vectors, as in `box_matrix`, and the determinant agrees
+        // with the volume.
+        let matrix = frame.box_matrix3();
+        for (row, vector) in [a, b, c].into_iter().enumerate() {
+            assert_eq!(
+                matrix.row(row).transpose(),
+                nalgebra::Vector3::from(vector.to_array())
+            );
+        }
+        assert_eq!(matrix.determinant(), frame.box_volume());
+    }
+
+    #[test]
+    fn superpose_recovers_rigid_motion() {
+        // An asymmetric cloud of atoms, so the fit is unambiguous.
+        let reference = Frame {
+            positions: vec![
+                0.0, 0.0, 0.0, //
+                1.0, 0.0, 0.0, //
+                0.0, 2.0, 0.0, //
+                0.5, 0.5, 3.0, //
+                2.0, 1.0, 1.0, //
+                1.5, 2.5, 0.5, //
+            ],
+            ..Frame::default()
+        };
+
+        // A rigidly rotated and translated copy.
+        let rotation = Mat3::from_axis_angle(Vec3::new(1.0, 2.0, 0.5).normalize(), 1.1);
+        let translation = Vec3::new(4.0, -2.0, 7.5);
+        let mut moved = reference.clone();
+        for pos in moved.positions.chunks_exact_mut(3) {
+            let rotated = rotation * Vec3::from_slice(pos) + translation;
+            pos.copy_from_slice(&rotated.to_array());
+        }
+
+        let rmsd = moved.superpose(&reference, &AtomSelection::All);
+        assert!(rmsd < 1e-5, "rmsd after superposition was {rmsd}");
+        for (pos, pos_ref) in moved.coords().zip(reference.coords()) {
+            assert!((pos - pos_ref).length() < 1e-5);
+        }
+
+        // Fitting on a subset transforms the whole frame by the same rigid motion, so the
+        // unselected atoms land on the reference as well.
+        let mut moved = reference.clone();
+        for pos in moved.positions.chunks_exact_mut(3) {
+            let rotated = rotation * Vec3::from_slice(pos) + translation;
+            pos.copy_from_slice(&rotated.to_array());
+        }
+        let rmsd = moved.superpose(&reference, &AtomSelection::Until(3));
+        assert!(rmsd < 1e-5);
+        for (pos, pos_ref) in moved.coords().zip(reference.coords()) {
+            assert!((pos - pos_ref).length() < 1e-4);
+        }
+
+        // An empty selection leaves the frame untouched.
+        let mut untouched = reference.clone();
+        let rmsd = untouched.superpose(&reference, &AtomSelection::Mask(vec![].into()));
+        assert_eq!(rmsd, 0.0);
+        assert_eq!(untouched, reference);
+    }
+
+    #[test]
+    fn make_whole_across_boundaries() {
+        // A triclinic box: the c vector has a skew along x.
+        let boxvec = Mat3::from_cols(
+            Vec3::new(10.0, 0.0, 0.0),
+            Vec3::new(0.0, 10.0, 0.0),
+            Vec3::new(3.0, 0.0, 10.0),
+        );
+
+        // A diatomic straddling the x boundary, a second one straddling the skewed c boundary,
+        // and a lone unbonded atom.
+        let mut frame = Frame {
+            boxvec,
+            positions: vec![
+                0.4, 5.0, 5.0, // 0, bonded to 1 across x.
+                9.6, 5.0, 5.0, // 1
+                5.0, 5.0, 0.3, // 2, bonded to 3 across c.
+                8.0 - 0.6, 5.0, 9.7, // 3, i.e. atom 2 shifted by -c plus a small bond offset.
+                1.0, 1.0, 1.0, // 4, unbonded.
+            ],
+            ..Frame::default()
+        };
+        frame.make_whole(&[(0, 1), (2, 3)]);
+
+        let coords: Vec<Vec3> = frame.coords().collect();
+        // The diatomics are brought back within one bond length.
+        assert!(coords[0].distance(coords[1]) < 1.0);
+        assert!(coords[2].distance(coords[3]) < 1.0);
+        // The unbonded atom did not move.
+        assert_eq!(coords[4], Vec3::new(1.0, 1.0, 1.0));
+        // The box is untouched.
+        assert_eq!(frame.boxvec, boxvec);
+    }
+
+    #[test]
+    fn split_into_chunks() -> io::Result<()> {
+        let pid = std::process::id();
+        let times = [0.0, 1.0, 2.0, 5.0, 6.0];
+        let path = write_trajectory(
+            "split",
+            times
+                .iter()
+                .enumerate()
+                .map(|(step, &time)| test_frame(step as u32, time, 20)),
+        )?;
+        let expected = XTCReader::open(&path)?.read_all_frames()?;
+
+        // Splitting by frame count fills each chunk before starting the next.
+        let mut reader = XTCReader::open(&path)?;
+        let out_pattern =
+            |idx| std::env::temp_dir().join(format!("molly_split_{pid}_frames_{idx}.xtc"));
+        let chunks = reader.split_by_frames(2, out_pattern)?;
+        let _guards: Vec<TempPath> = chunks
+            .iter()
+            .map(|(path, _)| TempPath::adopt(path.clone()))
+            .collect();
+        let counts: Vec<usize> = chunks.iter().map(|(_, count)| *count).collect();
+        assert_eq!(counts, [2, 2, 1]);
+        // The concatenation of the chunks is the original frame sequence.
+        let mut frames = Vec::new();
+        for (path, _) in &chunks {
+            frames.extend(XTCReader::open(path)?.read_all_frames()?);
+        }
+        assert_eq!(frames, expected.to_vec());
+
+        // Splitting by time groups the frames into fixed windows from the first frame's time;
+        // the empty [4, 6) window produces no file.
+        let mut reader = XTCReader::open(&path)?;
+        let out_pattern =
+            |idx| std::env::temp_dir().join(format!("molly_split_{pid}_time_{idx}.xtc"));
+        let chunks = reader.split_by_time(2.0, out_pattern)?;
+        let _guards: Vec<TempPath> = chunks
+            .iter()
+            .map(|(path, _)| TempPath::adopt(path.clone()))
+            .collect();
+        let counts: Vec<usize> = chunks.iter().map(|(_, count)| *count).collect();
+        assert_eq!(counts, [2, 1, 1, 1]);
+        let mut frames = Vec::new();
+        for (path, _) in &chunks {
+            frames.extend(XTCReader::open(path)?.read_all_frames()?);
+        }
+        assert_eq!(frames, expected.to_vec());
+
+        Ok(())
+    }
+
+    #[test]
+    fn selected_coords_borrows_included_positions() {
+        let natoms = 10;
+        let frame = Frame {
+            positions: (0..3 * natoms).map(|v| v as f32).collect(),
+            ..Frame::default()
+        };
+
+        let mask: Vec<bool> = (0..natoms).map(|idx| idx % 3 == 0).collect();
+        let selection = AtomSelection::Mask(mask.clone().into());
+        let selected: Vec<&[f32; 3]> = frame.selected_coords(&selection).collect();
+        // Exactly the included positions are yielded, in order, straight from the buffer.
+        assert_eq!(selected.len(), selection.natoms_selected(natoms));
+        for (pos, idx) in selected.iter().zip((0..natoms).filter(|idx| mask[*idx])) {
+            assert_eq!(
+                pos.as_slice(),
+                &frame.positions[idx * 3..idx * 3 + 3],
+                "position {idx} does not match"
+            );
+        }
+
+        // The iteration stops at the selection's bound, rather than running over positions the
+        // selection says nothing about.
+        let bounded = AtomSelection::range(Some(2), 5, None);
+        let selected: Vec<&[f32; 3]> = frame.selected_coords(&bounded).collect();
+        assert_eq!(selected.len(), 3);
+        assert_eq!(selected[0], &[6.0, 7.0, 8.0]);
+
+        // An all-selection walks the whole frame without copying a thing.
+        assert_eq!(frame.selected_coords(&AtomSelection::All).count(), natoms);
+    }
+
+    #[test]
+    fn wrap_into_box() {
+        // A triclinic box: the c vector has a skew along x.
+        let boxvec = Mat3::from_cols(
+            Vec3::new(10.0, 0.0, 0.0),
+            Vec3::new(0.0, 10.0, 0.0),
+            Vec3::new(3.0, 0.0, 10.0),
+        );
+        let c = boxvec.col(2);
+
+        let inside = Vec3::new(5.0, 5.0, 5.0);
+        let mut frame = Frame {
+            boxvec,
+            positions: [
+                inside,          // 0, already inside.
+                inside + 2.0 * c, // 1, two box-lengths out along the skewed c vector.
+                inside - c,      // 2, one box-length out in the other direction.
+                Vec3::new(-2.0, 5.0, 5.0), // 3, slightly negative along x.
+            ]
+            .iter()
+            .flat_map(|pos| pos.to_array())
+            .collect(),
+            ..Frame::default()
+        };
+        frame.wrap_into_box(&AtomSelection::range(None, 3, None));
+
+        let coords: Vec<Vec3> = frame.coords().collect();
+        // The skewed images land exactly back on the inside position; plain orthorhombic
+        // clamping of the z component would leave a spurious x offset of two skews.
+        assert!(coords[0].distance(inside) < 1e-5);
+        assert!(coords[1].distance(inside) < 1e-5);
+        assert!(coords[2].distance(inside) < 1e-5);
+        // The atom beyond the selection did not move.
+        assert_eq!(coords[3], Vec3::new(-2.0, 5.0, 5.0));
+
+        // Wrapping everything also pulls the negative coordinate into [0, box).
+        frame.wrap_into_box(&AtomSelection::All);
+        let coords: Vec<Vec3> = frame.coords().collect();
+        assert!(coords[3].distance(Vec3::new(8.0, 5.0, 5.0)) < 1e-5);
+
+        // A zeroed box means no periodic images; the frame is left untouched.
+        let mut unperiodic = Frame {
+            boxvec: Mat3::ZERO,
+            positions: vec![12.0, -3.0, 7.0],
+            ..Frame::default()
+        };
+        unperiodic.wrap_into_box(&AtomSelection::All);
+        assert_eq!(unperiodic.positions, [12.0, -3.0, 7.0]);
+    }
+
+    #[test]
+    fn resilient_reading() -> io::Result<()> {
+        let path = write_trajectory("resilient", (0..4).map(|step| test_frame(step, 0.0, 30)))?;
+
+        // Clobber the header of the second frame, wrecking its magic number.
+        let mut reader = XTCReader::open(&path)?;
+        let ends = reader.determine_offsets_exclusive(None)?;
+        let mut bytes = std::fs::read(&path)?;
+        let second = ends[0] as usize;
+        bytes[second..second + 8].fill(0xff);
+        std::fs::write(&path, &bytes)?;
+
+        let mut reader = XTCReader::open(&path)?;
+        let mut frame = Frame::default();
+        assert_eq!(reader.read_frame_resilient(&mut frame)?, FrameReadOutcome::Read);
+        assert_eq!(frame.step, 0);
+        // The corrupt second frame is skipped over in its entirety.
+        assert_eq!(
+            reader.read_frame_resilient(&mut frame)?,
+            FrameReadOutcome::Skipped {
+                bytes: ends[1] - ends[0]
+            }
+        );
+        assert_eq!(reader.read_frame_resilient(&mut frame)?, FrameReadOutcome::Read);
+        assert_eq!(frame.step, 2);
+        assert_eq!(reader.read_frame_resilient(&mut frame)?, FrameReadOutcome::Read);
+        assert_eq!(frame.step, 3);
+        assert_eq!(reader.read_frame_resilient(&mut frame)?, FrameReadOutcome::Eof);
+
+        // A trajectory that is cut off mid-write yields its frames up to the truncation point.
+        bytes.truncate(ends[2] as usize + 10);
+        std::fs::write(&path, &bytes)?;
+        let mut reader = XTCReader::open(&path)?;
+        assert_eq!(reader.read_frame_resilient(&mut frame)?, FrameReadOutcome::Read);
+        assert_eq!(
+            reader.read_frame_resilient(&mut frame)?,
+            FrameReadOutcome::Skipped {
+                bytes: ends[1] - ends[0]
+            }
+        );
+        assert_eq!(reader.read_frame_resilient(&mut frame)?, FrameReadOutcome::Read);
+        assert_eq!(reader.read_frame_resilient(&mut frame)?, FrameReadOutcome::Eof);
+
+        Ok(())
+    }
+
+    #[test]
+    fn typed_errors() -> io::Result<()> {
+        let mut writer = XTCWriter::new(std::io::Cursor::new(Vec::new()));
+        writer.write_frame(&Frame {
+            precision: 1000.0,
+            positions: vec![0.0; 3 * 20],
+            ..Frame::default()
+        })?;
+        let bytes = writer.file.into_inner();
+
+        // A wrecked magic number surfaces as BadMagic, with the offending value.
+        let mut corrupt = bytes.clone();
+        corrupt[..4].copy_from_slice(&1234i32.to_be_bytes());
+        let mut frame = Frame::default();
+        let err = XTCReader::new(corrupt.as_slice())
+            .read_frame(&mut frame)
+            .unwrap_err();
+        assert!(matches!(err, Error::BadMagic { found: 1234 }));
+
+        // A mismatched repeated natoms value surfaces as UnexpectedNatoms.
+        let mut corrupt = bytes.clone();
+        corrupt[Header::SIZE - 4..Header::SIZE].copy_from_slice(&21u32.to_be_bytes());
+        let err = XTCReader::new(corrupt.as_slice())
+            .read_frame(&mut frame)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnexpectedNatoms {
+                expected: 20,
+                found: 21,
+                frame: None,
+            }
+        ));
+
+        // A file that ends partway through a frame surfaces as Truncated, which converts to an
+        // UnexpectedEof io::Error for callers that stick to io::Result.
+        let err = XTCReader::new(&bytes[..bytes.len() / 2])
+            .read_frame(&mut frame)
+            .unwrap_err();
+        assert!(matches!(err, Error::Truncated));
+        assert_eq!(
+            io::Error::from(err).kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn oversized_selection_rejected() -> io::Result<()> {
+        let path = write_trajectory("validate", [test_frame(0, 0.0, 40)])?;
+
+        let mut reader = XTCReader::open(&path)?;
+        // A mask built for a larger system is rejected up front, rather than producing undefined
+        // trailing positions.
+        let oversized = AtomSelection::Mask(selection::BitMask::new(60));
+        assert!(reader.set_atom_selection(oversized).is_err());
+        assert!(matches!(reader.atom_selection(), AtomSelection::All));
+
+        // A fitting selection is accepted and honored by the plain reading functions.
+        let mut mask = selection::BitMask::new(40);
+        for idx in [0, 7, 21] {
+            mask.set(idx, true);
+        }
+        reader.set_atom_selection(AtomSelection::Mask(mask))?;
+        let mut frame = Frame::default();
+        assert!(reader.read_frame_into(&mut frame)?);
+        assert_eq!(frame.natoms(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn angstrom_units() -> io::Result<()> {
+        let frame = Frame {
+            boxvec: Mat3::from_diagonal(Vec3::new(2.0, 3.0, 4.0)),
+            ..test_frame(0, 0.0, 40)
+        };
+        let path = write_trajectory("units", [frame])?;
+
+        let mut reader = XTCReader::open(&path)?;
+        let mut nm = Frame::default();
+        reader.read_frame(&mut nm)?;
+
+        let mut reader = XTCReader::open(&path)?;
+        reader.units = Units::Angstrom;
+        let mut angstrom = Frame::default();
+        reader.read_frame(&mut angstrom)?;
+
+        for (nm, angstrom) in nm.positions.iter().zip(&angstrom.positions) {
+            assert_eq!(nm * 10.0, *angstrom);
+        }
+        assert_eq!(nm.boxvec * 10.0, angstrom.boxvec);
+        // The volumes differ by a clean factor of 1000 between nm³ and Å³.
+        assert_eq!(nm.box_volume(), 24.0);
+        assert_eq!(angstrom.box_volume(), 24_000.0);
+        // The stored precision is reported as-is in both modes.
+        assert_eq!(nm.precision, 1000.0);
+        assert_eq!(angstrom.precision, 1000.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn progress_reporting() -> io::Result<()> {
+        let path = write_trajectory("progress", (0..4).map(|step| test_frame(step, 0.0, 40)))?;
+
+        let reports = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut reader = XTCReader::open(&path)?;
+        let sink = std::sync::Arc::clone(&reports);
+        reader.on_progress(move |progress| sink.lock().unwrap().push(progress));
+
+        let mut frame = Frame::default();
+        while reader.read_frame_into(&mut frame)? {}
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 4);
+        let file_len = std::fs::metadata(&path)?.len();
+        for (idx, report) in reports.iter().enumerate() {
+            assert_eq!(report.frames_read, idx + 1);
+            assert_eq!(report.file_len, Some(file_len));
+            if idx > 0 {
+                assert!(report.bytes_read > reports[idx - 1].bytes_read);
+            }
+        }
+        // The byte accounting is exact: after the last frame, the whole file has been consumed.
+        assert_eq!(reports.last().unwrap().bytes_read, file_len);
+
+        Ok(())
+    }
+
+    #[test]
+    fn open_maybe_compressed_plain() -> io::Result<()> {
+        let path = write_trajectory("plain", (0..3).map(|step| test_frame(step, 0.0, 20)))?;
+
+        // A plain xtc file passes through the sniffing untouched.
+        let mut reader = XTCReader::open_maybe_compressed(&path)?;
+        let frames = reader.read_all_frames()?;
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[2].step, 2);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn open_maybe_compressed_gzip() -> io::Result<()> {
+        use std::io::Write;
+
+        // The compression is sniffed from the content, so the plain `.xtc` suffix is no problem.
+        let path = TempPath::new("gz");
+        let mut writer = XTCWriter::new(io::Cursor::new(Vec::new()));
+        for step in 0..3 {
+            writer.write_frame(&test_frame(step, step as f32, 40))?;
+        }
+        let bytes = writer.file.into_inner();
+
+        let expected = XTCReader::new(io::Cursor::new(bytes.clone())).read_all_frames()?;
+
+        let mut encoder =
+            flate2::write::GzEncoder::new(File::create(&path)?, flate2::Compression::default());
+        encoder.write_all(&bytes)?;
+        encoder.finish()?;
+
+        // The gzipped trajectory reads back to the exact same frames.
+        let mut reader = XTCReader::open_maybe_compressed(&path)?;
+        let frames = reader.read_all_frames()?;
+        assert_eq!(frames, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn constant_natoms_check() -> io::Result<()> {
+        // Two frames of different systems, concatenated into one stream.
+        let mut writer = XTCWriter::new(io::Cursor::new(Vec::new()));
+        for (step, natoms) in [(0, 20), (1, 30)] {
+            writer.write_frame(&Frame {
+                step,
+                time: step as f32,
+                precision: 1000.0,
+                positions: (0..3 * natoms).map(|v| v as f32 * 0.01).collect(),
+                ..Frame::default()
+            })?;
+        }
+        let bytes = writer.file.into_inner();
+
+        // Without the check, both frames read fine, with a silently changed atom count.
+        let mut reader = XTCReader::new(io::Cursor::new(bytes.clone()));
+        let mut frame = Frame::default();
+        reader.read_frame(&mut frame)?;
+        assert_eq!(frame.natoms(), 20);
+        reader.read_frame(&mut frame)?;
+        assert_eq!(frame.natoms(), 30);
+
+        // With it, the second frame is rejected the moment its header disagrees.
+        let mut reader = XTCReader::new(io::Cursor::new(bytes));
+        reader.expect_constant_natoms(true);
+        reader.read_frame(&mut frame)?;
+        let err = reader.read_frame(&mut frame).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnexpectedNatoms {
+                expected: 20,
+                found: 30,
+                frame: Some(1),
+            }
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn unwrap_step_across_overflow() -> io::Result<()> {
+        // Synthesize a trajectory whose raw steps cross the 32-bit boundary. The writer refuses
+        // steps beyond `i32::MAX`, so the stored step fields are patched in afterwards.
+        let raw_steps: [u32; 4] = [i32::MAX as u32 - 500, u32::MAX - 500, 500, 1500];
+        let mut writer = XTCWriter::new(io::Cursor::new(Vec::new()));
+        let mut offsets = Vec::new();
+        for (idx, _) in raw_steps.iter().enumerate() {
+            offsets.push(writer.file.position() as usize);
+            writer.write_frame(&Frame {
+                step: 0,
+                time: idx as f32,
+                precision: 1000.0,
+                positions: (0..3 * 20).map(|v| v as f32 * 0.01).collect(),
+                ..Frame::default()
+            })?;
+        }
+        let mut bytes = writer.file.into_inner();
+        for (&offset, &raw) in offsets.iter().zip(&raw_steps) {
+            // The step field sits 8 bytes into the frame header, after the magic and natoms.
+            bytes[offset + 8..offset + 12].copy_from_slice(&raw.to_be_bytes());
+        }
+
+        // Without the mode, the wrapped (negative) stored step is rejected.
+        let mut reader = XTCReader::new(io::Cursor::new(bytes.clone()));
+        let mut frame = Frame::default();
+        reader.read_frame(&mut frame)?;
+        assert!(matches!(
+            reader.read_frame(&mut frame),
+            Err(Error::StepOutOfRange { .. })
+        ));
+
+        // With it, the raw values are passed through and the reconstructed step keeps climbing.
+        let mut reader = XTCReader::new(io::Cursor::new(bytes));
+        reader.unwrap_step(true);
+        let mut unwrapped = Vec::new();
+        for &raw in &raw_steps {
+            reader.read_frame(&mut frame)?;
+            assert_eq!(frame.step, raw);
+            unwrapped.push(reader.unwrapped_step().unwrap());
+        }
+        assert!(unwrapped.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(unwrapped[2], (1 << 32) + 500);
+
+        // Going home resets the wrap tracking.
+        reader.home()?;
+        assert_eq!(reader.unwrapped_step(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn transform_recenters_frames() -> io::Result<()> {
+        let path = write_trajectory(
+            "transform",
+            (0..3).map(|step| Frame {
+                // Each frame sits at a different offset, so recentering actually does something.
+                positions: (0..3 * 40)
+                    .map(|v| (v % 3) as f32 + step as f32 * 5.0)
+                    .collect(),
+                ..test_frame(step, 0.0, 0)
+            }),
+        )?;
+
+        let mut reader = XTCReader::open(&path)?;
+        reader.with_transform(|frame| {
+            let [cx, cy, cz] = frame
+                .center_of_geometry(&AtomSelection::All)
+                .unwrap_or_default();
+            for position in frame.positions.chunks_exact_mut(3) {
+                position[0] -= cx;
+                position[1] -= cy;
+                position[2] -= cz;
+            }
+        });
+
+        let mut frame = Frame::default();
+        while reader.read_frame_into(&mut frame)? {
+            // Every frame comes out centered on its own centroid.
+            let center = frame.center_of_geometry(&AtomSelection::All).unwrap();
+            assert!(center.iter().all(|c| c.abs() < 1e-4), "center: {center:?}");
+        }
+
+        // Header-only scans remain untouched by the registered transform.
+        reader.home()?;
+        let header = reader.scan_header()?.unwrap();
+        assert_eq!(header.natoms, 40);
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_to_array() -> io::Result<()> {
+        let path = write_trajectory(
+            "array",
+            (0..5).map(|step| Frame {
+                positions: (0..3 * 40)
+                    .map(|v| (v + step as usize * 1000) as f32 * 0.01)
+                    .collect(),
+                ..test_frame(step, 0.0, 0)
+            }),
+        )?;
+
+        let mut reader = XTCReader::open(&path)?;
+        let frame_selection = FrameSelection::Range(selection::Range::new(
+            Some(1),
+            None,
+            Some(2.try_into().unwrap()),
+        ));
+        let atom_selection = AtomSelection::range(Some(10), 20, None);
+        let array = reader.read_to_array(&frame_selection, &atom_selection)?;
+        assert_eq!(array.shape(), (2, 10, 3));
+        assert_eq!(array.as_slice().len(), 2 * 10 * 3);
+
+        // The values must match the frames read through the regular path.
+        let mut frames = Vec::new();
+        reader.home()?;
+        reader.read_frames::<false>(&mut frames, &frame_selection, &atom_selection)?;
+        for (fidx, frame) in frames.iter().enumerate() {
+            for atom in 0..10 {
+                for dim in 0..3 {
+                    assert_eq!(array[[fidx, atom, dim]], frame.positions[atom * 3 + dim]);
+                }
+            }
+            assert_eq!(array.frame(fidx), &frame.positions[..]);
+        }
+
+        // Spot-check a couple of known coordinate values. Frame 0 of the array is trajectory
+        // frame 1, and atom 0 of the array is trajectory atom 10.
+        assert!((array[[0, 0, 0]] - 10.3).abs() < 1e-3);
+        assert!((array[[1, 4, 2]] - 30.44).abs() < 1e-3);
+
+        // The conversion into an `ndarray` hands over the storage with the same shape and
+        // values.
+        #[cfg(feature = "ndarray")]
+        {
+            let expected: Vec<f32> = array.as_slice().to_vec();
+            let array3: ndarray::Array3<f32> = array.into();
+            assert_eq!(array3.shape(), [2, 10, 3]);
+            assert!(array3.iter().eq(expected.iter()));
+            assert!((array3[[0, 0, 0]] - 10.3).abs() < 1e-3);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn center_and_bounding_box() {
+        let frame = Frame {
+            positions: vec![
+                0.0, 0.0, 0.0, //
+                1.0, 0.0, 0.0, //
+                0.0, 2.0, 0.0, //
+                1.0, 2.0, 4.0, //
+            ],
+            ..Frame::default()
+        };
+
+        assert_eq!(
+            frame.center_of_geometry(&AtomSelection::All),
+            Some([0.5, 1.0, 1.0])
+        );
+        assert_eq!(
+            frame.bounding_box(&AtomSelection::All),
+            Some(([0.0, 0.0, 0.0], [1.0, 2.0, 4.0]))
+        );
+
+        // A subselection only considers its included atoms.
+        let pair = AtomSelection::from_index_list(&[1, 3]);
+        assert_eq!(frame.center_of_geometry(&pair), Some([1.0, 1.0, 2.0]));
+        assert_eq!(
+            frame.bounding_box(&pair),
+            Some(([1.0, 0.0, 0.0], [1.0, 2.0, 4.0]))
+        );
+
+        // An empty selection yields no center or bounds at all.
+        let empty = AtomSelection::from_index_list(&[]);
+        assert_eq!(frame.center_of_geometry(&empty), None);
+        assert_eq!(frame.bounding_box(&empty), None);
+    }
+
+    #[test]
+    fn batch_read_into_flat_buffer() -> io::Result<()> {
+        let path = write_trajectory("batch", (0..5).map(|step| test_frame(step, 0.0, 40)))?;
+
+        let mut reader = XTCReader::open(&path)?;
+        reader.set_atom_selection(AtomSelection::Until(9))?;
+        let natoms = 9;
+
+        let mut buf = Vec::new();
+        assert_eq!(reader.read_frames_into(3, &mut buf)?, 3);
+        assert_eq!(buf.len(), 3 * natoms * 3);
+        // The remainder of the trajectory holds fewer frames than the batch asks for.
+        assert_eq!(reader.read_frames_into(3, &mut buf)?, 2);
+        assert_eq!(buf.len(), 5 * natoms * 3);
+        assert_eq!(reader.read_frames_into(3, &mut buf)?, 0);
+
+        // The batches must line up with the frames read one at a time.
+        reader.home()?;
+        let mut frame = Frame::default();
+        for chunk in buf.chunks_exact(natoms * 3) {
+            assert!(reader.read_frame_into(&mut frame)?);
+            assert_eq!(chunk, &frame.positions[..]);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn non_finite_coordinates() -> io::Result<()> {
+        // A small frame is stored as uncompressed floats, so a NaN survives the roundtrip.
+        let mut positions = vec![0.5; 5 * 3];
+        positions[3 * 3 + 1] = f32::NAN;
+        let mut writer = XTCWriter::new(std::io::Cursor::new(Vec::new()));
+        writer.write_frame(&Frame {
+            positions,
+            ..Frame::default()
+        })?;
+        let bytes = writer.file.into_inner();
+
+        // Without the check, the NaN is read back as-is.
+        let mut frame = Frame::default();
+        let mut reader = XTCReader::new(bytes.as_slice());
+        reader.read_frame(&mut frame)?;
+        assert!(frame.positions[3 * 3 + 1].is_nan());
+
+        // With the check, the offending frame and atom are identified.
+        let mut reader = XTCReader::new(bytes.as_slice());
+        reader.check_finite(true);
+        let err = reader.read_frame(&mut frame).unwrap_err();
+        assert!(matches!(err, Error::NonFiniteCoords { frame: 0, atom: 3 }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn count_without_decoding() -> io::Result<()> {
+        let path = write_trajectory("count", (0..7).map(|step| test_frame(step, 0.0, 30)))?;
+
+        let mut reader = XTCReader::open(&path)?;
+        assert_eq!(reader.count_frames()?, 7);
+
+        // Counting always covers the whole file and restores the reader's position.
+        let mut frame = Frame::default();
+        reader.read_frame(&mut frame)?;
+        reader.read_frame(&mut frame)?;
+        assert_eq!(reader.count_frames()?, 7);
+        reader.read_frame(&mut frame)?;
+        assert_eq!(frame.step, 2);
+        assert_eq!(reader.step, 3);
+
+        // A selection-aware count reports what the selection would actually yield.
+        let every_other = FrameSelection::All.downsample(2.try_into().unwrap());
+        assert_eq!(reader.count_selected_frames(&every_other)?, 4);
+        let first_100 = FrameSelection::Range(selection::Range::new(None, Some(100), None));
+        assert_eq!(reader.count_selected_frames(&first_100)?, 7);
+
+        Ok(())
+    }
+
+    #[test]
+    fn extract_subset_to_writer() -> io::Result<()> {
+        let path = write_trajectory(
+            "extract",
+            (0..10).map(|step| test_frame(step, step as f32, 40)),
+        )?;
+
+        // Keep atoms 5, 17, and 30 of every third frame.
+        let atoms = AtomSelection::from_index_list(&[5, 17, 30]);
+        let frames = FrameSelection::All.downsample(3.try_into().unwrap());
+        let mut reader = XTCReader::open(&path)?;
+        let mut out = XTCWriter::new(std::io::Cursor::new(Vec::new()));
+        let written = reader.extract_to(&mut out, &atoms, &frames)?;
+        assert_eq!(written, 4);
+
+        // Re-reading the output must yield exactly the selected atoms, in their original order.
+        reader.home()?;
+        let originals = reader.read_all_frames()?;
+        let mut extracted = XTCReader::new(std::io::Cursor::new(out.file.into_inner()));
+        let mut frame = Frame::default();
+        for idx in [0, 3, 6, 9] {
+            assert!(extracted.read_frame_into(&mut frame)?);
+            assert_eq!(frame.step, idx as u32);
+            assert_eq!(frame.natoms(), 3);
+            let original = &originals[idx];
+            for (nth, &atom) in [5usize, 17, 30].iter().enumerate() {
+                assert_eq!(
+                    frame.positions[nth * 3..nth * 3 + 3],
+                    original.positions[atom * 3..atom * 3 + 3],
+                );
+            }
+        }
+        assert!(!extracted.read_frame_into(&mut frame)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn frames_iterator() -> io::Result<()> {
+        let path = write_trajectory("frames_iter", (0..6).map(|step| test_frame(step, 0.0, 25)))?;
+
+        let mut reader = XTCReader::open(&path)?;
+        let steps: Vec<u32> = reader
+            .frames()
+            .map(|frame| Ok(frame?.step))
+            .collect::<io::Result<_>>()?;
+        assert_eq!(steps, [0, 1, 2, 3, 4, 5]);
+
+        // With an atom selection, each frame only holds the selected positions.
+        reader.home()?;
+        for frame in reader.frames_with_selection(AtomSelection::Until(10)) {
+            assert_eq!(frame?.natoms(), 10);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_from_in_memory_bytes() -> io::Result<()> {
+        // Write a trajectory into memory; no filesystem is involved anywhere.
+        let mut writer = XTCWriter::new(io::Cursor::new(Vec::new()));
+        for step in 0..5 {
+            writer.write_frame(&Frame {
+                step,
+                time: step as f32 * 2.0,
+                precision: 1000.0,
+                positions: (0..3 * 30).map(|v| (v + step as usize) as f32 * 0.01).collect(),
+                ..Frame::default()
+            })?;
+        }
+        let bytes = writer.file.into_inner();
+
+        // Any `Read + Seek` source offers the full reading API, including the seek-based
+        // functions that go beyond plain sequential reads.
+        let mut reader = XTCReader::new(io::Cursor::new(bytes));
+        reader.set_atom_selection(AtomSelection::Until(10))?;
+        let mut frame = Frame::default();
+        let mut nframes = 0;
+        while reader.read_frame_into(&mut frame)? {
+            assert_eq!(frame.natoms(), 10);
+            nframes += 1;
+        }
+        assert_eq!(nframes, 5);
+
+        reader.home()?;
+        assert_eq!(reader.seek_to_time(4.0)?, Some(2));
+        let mut frame = Frame::default();
+        reader.read_frame(&mut frame)?;
+        assert_eq!(frame.step, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn query_iterator() -> io::Result<()> {
+        let path = write_trajectory("query", (0..6).map(|step| test_frame(step, 0.0, 25)))?;
+
+        // A query names both axes of a selection as one reusable value.
+        let query = Query::new()
+            .frames(FrameSelection::Range(selection::Range::new(
+                Some(1),
+                Some(6),
+                Some(2.try_into().unwrap()),
+            )))
+            .atoms(AtomSelection::Until(10));
+
+        let mut reader = XTCReader::open(&path)?;
+        let frames: Vec<Frame> = reader.read(&query).collect::<Result<_, _>>()?;
+        let steps: Vec<u32> = frames.iter().map(|frame| frame.step).collect();
+        assert_eq!(steps, [1, 3, 5]);
+        for frame in &frames {
+            assert_eq!(frame.natoms(), 10);
+        }
+
+        // `Query::all` matches the default behavior of the plain reading functions.
+        reader.home()?;
+        let all: Vec<Frame> = reader.read(&Query::all()).collect::<Result<_, _>>()?;
+        assert_eq!(all.len(), 6);
+        assert_eq!(all[5].natoms(), 25);
+
+        Ok(())
     }
 }
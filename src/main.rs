@@ -328,7 +328,7 @@ fn main() -> std::io::Result<()> {
             .iter()
             .map(|&offset| -> io::Result<Header> {
                 reader.file.seek(SeekFrom::Start(offset))?;
-                reader.read_header()
+                Ok(reader.read_header()?)
             })
             .collect::<io::Result<Vec<_>>>()?;
         let natoms = headers
@@ -0,0 +1,281 @@
+//! A memory-mapped xtc reader.
+//!
+//! For interactive tools that repeatedly jump around a large trajectory, going through
+//! [`std::io`] costs a syscall per seek and per read. [`XTCMmapReader`] maps the whole file into
+//! memory once, after which seeking is nothing more than moving a cursor over a byte slice. It
+//! shines in combination with an [`XTCIndex`](crate::XTCIndex) for random access.
+//!
+//! This module is only available on unix targets, behind the `mmap` feature.
+
+use std::fs::File;
+use std::io::{self, Cursor};
+use std::ops::Deref;
+use std::os::fd::AsRawFd;
+use std::path::Path;
+
+use crate::selection::AtomSelection;
+use crate::{Frame, FrameHeader, Header, XTCReader};
+
+/// A read-only memory mapping of a [`File`].
+///
+/// The mapping is released when this value is dropped.
+struct Mmap {
+    ptr: *const u8,
+    len: usize,
+}
+
+impl Mmap {
+    /// Map `file` into memory in its entirety.
+    fn map(file: &File) -> io::Result<Self> {
+        let len = file.metadata()?.len() as usize;
+        if len == 0 {
+            // Mapping zero bytes is an error, but an empty trajectory is not.
+            return Ok(Self {
+                ptr: std::ptr::NonNull::dangling().as_ptr(),
+                len: 0,
+            });
+        }
+        // SAFETY: We pass a valid file descriptor and a length that matches the file size. The
+        // mapping is private and read-only, and is unmapped exactly once, on drop.
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self {
+            ptr: ptr as *const u8,
+            len,
+        })
+    }
+}
+
+impl Deref for Mmap {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // SAFETY: `ptr` points to a live mapping of `len` bytes (or is dangling with a len of
+        // zero, for which `from_raw_parts` is fine).
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        if self.len > 0 {
+            // SAFETY: `ptr` and `len` describe the mapping we created in `Mmap::map`.
+            unsafe { libc::munmap(self.ptr as *mut libc::c_void, self.len) };
+        }
+    }
+}
+
+// SAFETY: The mapping is read-only, so sharing and sending it between threads is fine.
+unsafe impl Send for Mmap {}
+unsafe impl Sync for Mmap {}
+
+/// An [`XTCReader`] over a memory-mapped trajectory file.
+///
+/// Reads directly from the mapped byte slice, such that seeking—in particular the random access
+/// patterns of [`XTCIndex`](crate::XTCIndex)-driven tools—does not involve any syscalls.
+///
+/// # Safety
+///
+/// The underlying file must not be modified (truncated, rewritten) by this or any other process
+/// while it is mapped. Doing so does not cause memory unsafety within this crate's API, but on
+/// most platforms a read from a mapped page whose file content has been truncated away aborts the
+/// process with a bus error.
+pub struct XTCMmapReader {
+    map: Mmap,
+    /// The position of the reader within the mapped file, in bytes.
+    pub pos: u64,
+    /// The number of frames that have been read.
+    pub step: usize,
+}
+
+impl XTCMmapReader {
+    /// Map the trajectory file at `path` and return a reader over it.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        Ok(Self {
+            map: Mmap::map(&file)?,
+            pos: 0,
+            step: 0,
+        })
+    }
+
+    /// Returns the raw bytes of the mapped trajectory.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// Run `operation` on an [`XTCReader`] cursored over the mapped bytes, carrying the position
+    /// and step of this reader over to it and back.
+    fn with_reader<T>(
+        &mut self,
+        operation: impl FnOnce(&mut XTCReader<Cursor<&[u8]>>) -> Result<T, crate::Error>,
+    ) -> io::Result<T> {
+        let mut reader = XTCReader::new(Cursor::new(&self.map[..]));
+        reader.file.set_position(self.pos);
+        reader.step = self.step;
+        let result = operation(&mut reader);
+        self.pos = reader.file.position();
+        self.step = reader.step;
+        Ok(result?)
+    }
+
+    /// Reads and returns a [`Frame`] and advances one step.
+    ///
+    /// See [`XTCReader::read_frame`].
+    pub fn read_frame(&mut self, frame: &mut Frame) -> io::Result<()> {
+        self.with_reader(|reader| reader.read_frame(frame))
+    }
+
+    /// Reads and returns a [`Frame`] according to the [`AtomSelection`], and advances one step.
+    ///
+    /// See [`XTCReader::read_frame_with_selection`].
+    pub fn read_frame_with_selection(
+        &mut self,
+        frame: &mut Frame,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<()> {
+        self.with_reader(|reader| reader.read_frame_with_selection(frame, atom_selection))
+    }
+
+    /// Read the next frame into `frame`, reusing its allocations.
+    ///
+    /// See [`XTCReader::read_frame_into`].
+    pub fn read_frame_into(&mut self, frame: &mut Frame) -> io::Result<bool> {
+        self.with_reader(|reader| reader.read_frame_into(frame))
+    }
+
+    /// Read the next frame into `frame` according to an [`AtomSelection`], reusing its
+    /// allocations.
+    ///
+    /// See [`XTCReader::read_frame_into_with_selection`].
+    pub fn read_frame_into_with_selection(
+        &mut self,
+        frame: &mut Frame,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<bool> {
+        self.with_reader(|reader| reader.read_frame_into_with_selection(frame, atom_selection))
+    }
+
+    /// Seeks to `offset`, then reads and returns a [`Frame`] and advances one step.
+    ///
+    /// The offsets stored in an [`XTCIndex`](crate::XTCIndex) can be passed here directly.
+    pub fn read_frame_at_offset(
+        &mut self,
+        frame: &mut Frame,
+        offset: u64,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<()> {
+        self.pos = offset;
+        self.read_frame_with_selection(frame, atom_selection)
+    }
+
+    /// Read the metadata of the frame at the current position without decoding its positions.
+    ///
+    /// Returns [`None`] once the end of the trajectory is reached. Since no data needs to be
+    /// touched beyond the header and the stored byte counts, this skips through a mapped
+    /// trajectory very quickly.
+    pub fn scan_header(&mut self) -> io::Result<Option<FrameHeader>> {
+        let bytes = &self.map[..];
+        if self.pos as usize >= bytes.len() {
+            return Ok(None);
+        }
+        let mut cursor = Cursor::new(bytes);
+        cursor.set_position(self.pos);
+
+        let header = match Header::read(&mut cursor) {
+            Ok(header) => header,
+            Err(crate::Error::Truncated) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+
+        let precision = if header.natoms <= 9 {
+            cursor.set_position(cursor.position() + header.natoms as u64 * 3 * 4);
+            None
+        } else {
+            let precision = crate::reader::read_f32(&mut cursor)?;
+            cursor.set_position(cursor.position() + crate::reader::NBYTES_POSITIONS_PRELUDE as u64);
+            let nbytes = crate::reader::read_nbytes(&mut cursor, header.magic)?;
+            cursor.set_position(cursor.position() + (nbytes + crate::padding(nbytes)) as u64);
+            Some(precision)
+        };
+
+        self.pos = cursor.position();
+        self.step += 1;
+
+        Ok(Some(FrameHeader {
+            natoms: header.natoms,
+            step: header.step,
+            time: header.time,
+            boxvec: header.boxvec,
+            precision,
+        }))
+    }
+
+    /// Reset the reader to its initial position.
+    ///
+    /// Go back to the first frame.
+    pub fn home(&mut self) {
+        self.pos = 0;
+        self.step = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{test_frame, write_trajectory, TempPath};
+
+    #[test]
+    fn matches_file_reader() -> io::Result<()> {
+        let path = write_trajectory(
+            "mmap",
+            (0..4).map(|step| test_frame(step, step as f32 * 0.5, 40)),
+        )?;
+
+        let mut file_reader = XTCReader::open(&path)?;
+        let mut mmap_reader = XTCMmapReader::open(&path)?;
+
+        let mut expected = Frame::default();
+        let mut mapped = Frame::default();
+        while file_reader.read_frame_into(&mut expected)? {
+            assert!(mmap_reader.read_frame_into(&mut mapped)?);
+            assert_eq!(mapped, expected);
+        }
+        assert!(!mmap_reader.read_frame_into(&mut mapped)?);
+        assert_eq!(mmap_reader.step, 4);
+
+        // Scanning headers over the map sees the same metadata.
+        mmap_reader.home();
+        let mut times = Vec::new();
+        while let Some(header) = mmap_reader.scan_header()? {
+            times.push(header.time);
+        }
+        assert_eq!(times, [0.0, 0.5, 1.0, 1.5]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn empty_file() -> io::Result<()> {
+        let path = TempPath::new("mmap_empty");
+        std::fs::write(&path, [])?;
+
+        let mut reader = XTCMmapReader::open(&path)?;
+        assert!(reader.as_bytes().is_empty());
+        assert!(!reader.read_frame_into(&mut Frame::default())?);
+        assert!(reader.scan_header()?.is_none());
+
+        Ok(())
+    }
+}
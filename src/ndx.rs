@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+use crate::selection::AtomSelection;
+
+/// Parse a GROMACS `.ndx` index file into selections keyed by group name.
+///
+/// Groups are declared as `[ Name ]` headers followed by whitespace-delimited atom indices, which
+/// may wrap over any number of lines. Blank lines and `;` comments are ignored. The 1-based
+/// indices of the file are converted to molly's 0-based convention.
+///
+/// # Errors
+///
+/// Next to passing through any reader errors, this function will return an error for indices that
+/// do not parse as numbers, for the invalid index 0, and for indices that appear before any group
+/// header.
+pub fn parse_ndx<R: Read>(reader: R) -> io::Result<HashMap<String, AtomSelection>> {
+    let mut groups: Vec<(String, Vec<u32>)> = Vec::new();
+
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        // Strip any comment from the line.
+        let line = match line.split_once(';') {
+            Some((before, _comment)) => before,
+            None => &line,
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            groups.push((name.trim().to_string(), Vec::new()));
+            continue;
+        }
+
+        let Some((_, indices)) = groups.last_mut() else {
+            return Err(io::Error::other(format!(
+                "found indices before any group header in ndx file: {line:?}"
+            )));
+        };
+        for word in line.split_whitespace() {
+            let index: u32 = word.parse().map_err(|err| {
+                io::Error::other(format!("could not parse ndx index {word:?}: {err}"))
+            })?;
+            if index == 0 {
+                return Err(io::Error::other(
+                    "found index 0 in ndx file, but ndx indices are 1-based",
+                ));
+            }
+            // Convert from the 1-based ndx convention to our 0-based indices.
+            indices.push(index - 1);
+        }
+    }
+
+    Ok(groups
+        .into_iter()
+        .map(|(name, indices)| (name, AtomSelection::from_index_list(&indices)))
+        .collect())
+}
+
+/// Parse the `.ndx` index file at the provided path.
+///
+/// See [`parse_ndx`].
+pub fn parse_ndx_file<P: AsRef<Path>>(path: P) -> io::Result<HashMap<String, AtomSelection>> {
+    parse_ndx(File::open(path)?)
+}
+
+impl AtomSelection {
+    /// Read the group named `group` from the GROMACS `.ndx` index file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Next to the errors described on [`parse_ndx`], this function will return an error if the
+    /// file does not contain the requested group.
+    pub fn from_ndx_group<P: AsRef<Path>>(path: P, group: &str) -> io::Result<Self> {
+        let path = path.as_ref();
+        parse_ndx_file(path)?.remove(group).ok_or_else(|| {
+            io::Error::other(format!("ndx file {path:?} has no group named {group:?}"))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NDX: &str = "\
+; A comment line.
+[ System ]
+1 2 3 4 5
+6 7    8
+[ Protein ]
+2 4 ; a trailing comment
+6
+
+[Empty]
+";
+
+    #[test]
+    fn parse_groups() -> io::Result<()> {
+        let groups = parse_ndx(NDX.as_bytes())?;
+        assert_eq!(groups.len(), 3);
+
+        let system = &groups["System"];
+        assert_eq!(system.to_index_list(100), Vec::from_iter(0..8));
+
+        // The 1-based ndx indices become 0-based.
+        let protein = &groups["Protein"];
+        assert_eq!(protein.to_index_list(100), [1, 3, 5]);
+
+        let empty = &groups["Empty"];
+        assert!(empty.to_index_list(100).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        // Indices are 1-based, so 0 is invalid.
+        assert!(parse_ndx("[ System ]\n0 1 2".as_bytes()).is_err());
+        // Non-numeric indices are invalid.
+        assert!(parse_ndx("[ System ]\n1 2 three".as_bytes()).is_err());
+        // Indices must belong to a group.
+        assert!(parse_ndx("1 2 3".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+//! Parallel frame decoding.
+//!
+//! Decompressing distinct frames is embarrassingly parallel: each frame is a self-contained
+//! compressed block, so given an [`XTCIndex`] with the frame offsets, any number of workers can
+//! decode disjoint frames independently. [`read_frames_parallel`] spreads the frames of a
+//! [`FrameSelection`] over a pool of scoped threads, each with its own file handle, and scales
+//! near-linearly with cores until disk bandwidth takes over.
+
+use std::io;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::index::XTCIndex;
+use crate::selection::{AtomSelection, FrameSelection};
+use crate::{Frame, XTCReader};
+
+/// Read the frames selected by `frame_selection` from the trajectory at `path`, decoding them in
+/// parallel according to an [`XTCIndex`].
+///
+/// Each worker thread opens its own handle onto the trajectory and decodes the frames it claims
+/// independently. The returned frames are tagged with their index within the trajectory and
+/// sorted by it, so the trajectory order is already reassembled.
+///
+/// The number of worker threads defaults to the available parallelism, and can be pinned with
+/// `nthreads`.
+///
+/// # Errors
+///
+/// Next to passing through any reader errors, this function will return an error if the index
+/// does not match the trajectory.
+///
+/// # Panics
+///
+/// If a worker thread panics, the panic is propagated.
+pub fn read_frames_parallel<P: AsRef<Path>>(
+    path: P,
+    index: &XTCIndex,
+    frame_selection: &FrameSelection,
+    atom_selection: &AtomSelection,
+    nthreads: Option<NonZeroUsize>,
+) -> io::Result<Vec<(usize, Frame)>> {
+    let path = path.as_ref();
+
+    // The work list: the index and offset of each selected frame.
+    let mut work = Vec::new();
+    for (idx, entry) in index.frames.iter().enumerate() {
+        match frame_selection.is_included(idx) {
+            Some(true) => work.push((idx, entry.offset)),
+            Some(false) => {}
+            // The selection holds no frames beyond this point.
+            None => break,
+        }
+    }
+
+    let nthreads = nthreads
+        .or_else(|| std::thread::available_parallelism().ok())
+        .map_or(1, NonZeroUsize::get)
+        .min(work.len().max(1));
+
+    // Workers claim frames off this shared cursor, so that a slow frame does not stall the
+    // distribution of the remaining work.
+    let cursor = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(work.len()));
+
+    std::thread::scope(|scope| -> io::Result<()> {
+        let mut workers = Vec::with_capacity(nthreads);
+        for _ in 0..nthreads {
+            workers.push(scope.spawn(|| -> io::Result<()> {
+                let mut reader = XTCReader::open(path)?;
+                let mut local = Vec::new();
+                loop {
+                    let claimed = cursor.fetch_add(1, Ordering::Relaxed);
+                    let Some(&(idx, offset)) = work.get(claimed) else {
+                        break;
+                    };
+                    let mut frame = Frame::default();
+                    reader.read_frame_at_offset::<false>(&mut frame, offset, atom_selection)?;
+                    local.push((idx, frame));
+                }
+                results.lock().unwrap().extend(local);
+                Ok(())
+            }));
+        }
+        for worker in workers {
+            worker.join().expect("a frame decoding thread panicked")?;
+        }
+        Ok(())
+    })?;
+
+    let mut frames = results.into_inner().unwrap();
+    frames.sort_unstable_by_key(|&(idx, _)| idx);
+    Ok(frames)
+}
+
+/// The [`ParallelIterator`](rayon::iter::ParallelIterator) counterpart to
+/// [`read_frames_parallel`], for composing with `rayon` pipelines.
+///
+/// Rather than collecting the decoded frames, this hands them to the caller tagged with their
+/// index within the trajectory, so further per-frame work (analysis, filtering, reduction) runs
+/// inside the same thread pool without an intermediate allocation. The frames arrive in no
+/// particular order; sort by the tag to reassemble the trajectory order.
+///
+/// Each rayon worker opens its own handle onto the trajectory when it claims its first frame, and
+/// any errors—from opening the file or from decoding—surface per item.
+#[cfg(feature = "rayon")]
+pub fn par_read_frames(
+    path: impl AsRef<Path>,
+    index: &XTCIndex,
+    frame_selection: &FrameSelection,
+    atom_selection: &AtomSelection,
+) -> impl rayon::iter::ParallelIterator<Item = io::Result<(usize, Frame)>> {
+    use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+    let path = path.as_ref().to_path_buf();
+    let atom_selection = atom_selection.clone();
+
+    // The work list: the index and offset of each selected frame.
+    let mut work = Vec::new();
+    for (idx, entry) in index.frames.iter().enumerate() {
+        match frame_selection.is_included(idx) {
+            Some(true) => work.push((idx, entry.offset)),
+            Some(false) => {}
+            // The selection holds no frames beyond this point.
+            None => break,
+        }
+    }
+
+    work.into_par_iter().map_init(
+        move || XTCReader::open(&path),
+        move |reader, (idx, offset)| {
+            // An error from opening the file handle is reported for every frame the worker
+            // claims; `io::Error` does not clone, so the kind and message are carried over.
+            let reader = reader
+                .as_mut()
+                .map_err(|err| io::Error::new(err.kind(), err.to_string()))?;
+            let mut frame = Frame::default();
+            reader.read_frame_at_offset::<false>(&mut frame, offset, &atom_selection)?;
+            Ok((idx, frame))
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{test_frame, write_trajectory};
+
+    #[test]
+    fn matches_serial_reading() -> io::Result<()> {
+        let path = write_trajectory(
+            "parallel",
+            (0..20).map(|step| test_frame(step, step as f32, 60)),
+        )?;
+
+        let mut reader = XTCReader::open(&path)?;
+        let index = reader.build_index()?;
+        let expected = reader.read_all_frames()?;
+
+        let frames = read_frames_parallel(
+            &path,
+            &index,
+            &FrameSelection::All,
+            &AtomSelection::All,
+            NonZeroUsize::new(4),
+        )?;
+        assert_eq!(frames.len(), 20);
+        for (idx, frame) in &frames {
+            assert_eq!(frame, &expected[*idx]);
+        }
+
+        // A frame selection only decodes (and tags) the selected frames.
+        let every_fifth = FrameSelection::All.downsample(5.try_into().unwrap());
+        let frames =
+            read_frames_parallel(&path, &index, &every_fifth, &AtomSelection::All, None)?;
+        let indices: Vec<usize> = frames.iter().map(|&(idx, _)| idx).collect();
+        assert_eq!(indices, [0, 5, 10, 15]);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_iterator_matches_serial_reading() -> io::Result<()> {
+        use rayon::iter::ParallelIterator;
+
+        let path = write_trajectory(
+            "par_iter",
+            (0..20).map(|step| test_frame(step, step as f32, 60)),
+        )?;
+
+        let mut reader = XTCReader::open(&path)?;
+        let index = reader.build_index()?;
+        let expected = reader.read_all_frames()?;
+
+        let every_fifth = FrameSelection::All.downsample(5.try_into().unwrap());
+        let mut frames = par_read_frames(&path, &index, &every_fifth, &AtomSelection::All)
+            .collect::<io::Result<Vec<_>>>()?;
+        frames.sort_unstable_by_key(|&(idx, _)| idx);
+        let indices: Vec<usize> = frames.iter().map(|&(idx, _)| idx).collect();
+        assert_eq!(indices, [0, 5, 10, 15]);
+        for (idx, frame) in &frames {
+            assert_eq!(frame, &expected[*idx]);
+        }
+
+        Ok(())
+    }
+}
@@ -29,9 +29,18 @@ pub const NBYTES_POSITIONS_PRELUDE: usize = 7 * 4;
 #[inline]
 /// The low-level decompression routine.
 ///
-/// If successful, returns the number of compressed bytes that were read.
+/// If successful, returns the number of bytes that were consumed from the reader, including the
+/// prelude, the stored byte count, and the padding of the compressed block.
 ///
 /// `header_natoms` must be greater than or equal to the number of `positions`.
+///
+/// # Note
+///
+/// The compressed stream is delta-coded, so the integer coordinates of every position up to the
+/// last selected index must be decoded in sequence—there is no way to jump into the middle of a
+/// frame. For positions before the first selected index, the float reconstruction and the
+/// selection lookup are skipped behind a single comparison, but the bit unpacking itself remains;
+/// a selection that starts deep into a large frame still pays for decoding everything before it.
 pub fn read_compressed_positions<'s, 'r, B: Buffered<'s, 'r, R>, R: Read>(
     file: &'r mut R,
     header_natoms: usize,
@@ -109,6 +118,9 @@ pub fn read_compressed_positions<'s, 'r, B: Buffered<'s, 'r, R>, R: Read>(
     // The number of positions to be read to fulfill an AtomSelection may not be equal to natoms!
     assert!(header_natoms >= natoms_out);
     let limit = atom_selection.reading_limit(header_natoms);
+    // Positions before this index can never be included, so they take a cheap discard path that
+    // skips the float reconstruction and the selection lookup.
+    let first_included = atom_selection.first_included();
     'decompress: while read_idx < limit {
         let mut coord = [0i32; 3];
         let Some(mut position) = positions
@@ -133,7 +145,11 @@ pub fn read_compressed_positions<'s, 'r, B: Buffered<'s, 'r, R>, R: Read>(
 
         macro_rules! write_position {
             ($position:ident, $write_idx:ident, $read_idx:ident, $coord:ident) => {
-                let is_included = atom_selection.is_included($read_idx);
+                let is_included = if $read_idx < first_included {
+                    Some(false)
+                } else {
+                    atom_selection.is_included($read_idx)
+                };
                 $read_idx += 1;
                 match is_included {
                     None => break 'decompress,
@@ -238,8 +254,15 @@ pub fn read_compressed_positions<'s, 'r, B: Buffered<'s, 'r, R>, R: Read>(
         )
     }
 
-    // The number of bytes that were read during decompression.
-    let nbytes = buffer.tell();
+    // The number of bytes consumed from the reader: the prelude, the stored byte count, and the
+    // compressed block including its padding. The decode loop may stop early when a selection is
+    // exhausted, but `finish` leaves the reader at the end of the block either way.
+    let nbytes = NBYTES_POSITIONS_PRELUDE
+        + match magic {
+            Magic::Xtc1995 => 4,
+            Magic::Xtc2023 => 8,
+        }
+        + buffer.size();
     buffer.finish()?;
 
     Ok(nbytes)
@@ -297,7 +320,7 @@ pub fn read_nbytes<R: Read>(reader: &mut R, magic: Magic) -> io::Result<usize> {
     Ok(nbytes)
 }
 
-fn calc_sizeint(
+pub(crate) fn calc_sizeint(
     minint: [i32; 3],
     maxint: [i32; 3],
     sizeint: &mut [u32; 3],
@@ -321,7 +344,7 @@ fn calc_sizeint(
 }
 
 #[inline]
-const fn sizeofint(size: u32) -> u32 {
+pub(crate) const fn sizeofint(size: u32) -> u32 {
     let mut n = 1;
     let mut nbits = 0;
 
@@ -333,7 +356,7 @@ const fn sizeofint(size: u32) -> u32 {
     nbits
 }
 
-fn sizeofints(sizes: [u32; 3]) -> u32 {
+pub(crate) fn sizeofints(sizes: [u32; 3]) -> u32 {
     let mut nbytes = 1;
     let mut bytes = [0u8; 32];
     bytes[0] = 1;
@@ -712,14 +735,14 @@ mod tests {
             };
 
             // All positions should be correct.
-            let selection = AtomSelection::Mask(vec![true; N_ATOMS]);
+            let selection = AtomSelection::Mask(vec![true; N_ATOMS].into());
             let positions = read_positions(selection)?;
             assert_eq!(positions.len(), N_ATOMS * 3); // We know this but still.
             assert_eq!(positions.len(), CORRECT_POSITIONS.len());
             assert_eq!(positions, CORRECT_POSITIONS);
 
             // All positions should be NaN, since the selection is empty.
-            let selection = AtomSelection::Mask(vec![false; N_ATOMS]);
+            let selection = AtomSelection::Mask(vec![false; N_ATOMS].into());
             let positions = read_positions(selection)?;
             assert_eq!(positions.len(), N_ATOMS * 3);
             assert_eq!(positions.len(), CORRECT_POSITIONS.len());
@@ -728,7 +751,7 @@ mod tests {
             // With the interleaved selection, we expect a correct position followed by a NaN,
             // repeated.
             let interleaved = Vec::from_iter((0..N_ATOMS as u32).map(|i| i % 2 == 0));
-            let selection = AtomSelection::Mask(interleaved);
+            let selection = AtomSelection::Mask(interleaved.into());
             let positions = read_positions(selection)?;
             assert_eq!(positions.len(), N_ATOMS * 3);
             assert_eq!(positions.len(), CORRECT_POSITIONS.len());
@@ -757,7 +780,7 @@ mod tests {
             .into_iter()
             .flatten()
             .collect();
-            let selection = AtomSelection::Mask(mask.clone());
+            let selection = AtomSelection::Mask(mask.clone().into());
             let positions = read_positions(selection)?;
             assert_eq!(positions.len(), N_ATOMS * 3);
             assert_eq!(positions.len(), CORRECT_POSITIONS.len());
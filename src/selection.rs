@@ -1,5 +1,170 @@
 use std::collections::BTreeSet;
-use std::num::NonZeroU64;
+use std::num::{NonZeroU32, NonZeroU64};
+
+/// A bitmap over atom indices.
+///
+/// This backs the [`AtomSelection::Mask`] variant. Compared to a `Vec<bool>`, the `u64` words
+/// store the inclusion of 64 atoms per 8 bytes rather than one, which matters when several
+/// selections over multi-million atom systems are held at once.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BitMask {
+    words: Vec<u64>,
+    /// The number of valid bits in `words`.
+    len: usize,
+}
+
+impl BitMask {
+    /// The number of bits per word.
+    const BITS: usize = u64::BITS as usize;
+
+    /// Create a new [`BitMask`] of `len` bits, all of which are unset.
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(Self::BITS)],
+            len,
+        }
+    }
+
+    /// Returns the number of bits in this [`BitMask`].
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the bit at `idx`, or [`None`] if `idx` lies beyond the mask.
+    pub fn get(&self, idx: usize) -> Option<bool> {
+        if idx >= self.len {
+            return None;
+        }
+        Some(self.words[idx / Self::BITS] >> (idx % Self::BITS) & 1 == 1)
+    }
+
+    /// Set the bit at `idx`.
+    ///
+    /// # Panics
+    ///
+    /// If `idx` lies beyond the mask, this function panics.
+    pub fn set(&mut self, idx: usize, value: bool) {
+        assert!(
+            idx < self.len,
+            "the index ({idx}) is outside the defined range of the mask (..{})",
+            self.len
+        );
+        let bit = 1 << (idx % Self::BITS);
+        if value {
+            self.words[idx / Self::BITS] |= bit;
+        } else {
+            self.words[idx / Self::BITS] &= !bit;
+        }
+    }
+
+    /// Returns the index of the last set bit, if any bit is set at all.
+    pub fn last_set(&self) -> Option<usize> {
+        self.words
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, &word)| word != 0)
+            .map(|(idx, word)| idx * Self::BITS + (Self::BITS - 1 - word.leading_zeros() as usize))
+    }
+
+    /// Returns the index of the first set bit, if any bit is set at all.
+    pub fn first_set(&self) -> Option<usize> {
+        self.words
+            .iter()
+            .enumerate()
+            .find(|(_, &word)| word != 0)
+            .map(|(idx, word)| idx * Self::BITS + word.trailing_zeros() as usize)
+    }
+
+    /// Returns the number of set bits among the first `limit` bits.
+    pub fn count_ones_below(&self, limit: usize) -> usize {
+        let limit = usize::min(limit, self.len);
+        let (full_words, partial_bits) = (limit / Self::BITS, limit % Self::BITS);
+        let full: usize = self.words[..full_words]
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum();
+        let partial = if partial_bits > 0 {
+            (self.words[full_words] & ((1 << partial_bits) - 1)).count_ones() as usize
+        } else {
+            0
+        };
+        full + partial
+    }
+
+    /// Returns an iterator over the bits in this [`BitMask`].
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|idx| self.get(idx).unwrap())
+    }
+}
+
+/// The serialized form of a [`BitMask`]: the number of bits, and the indices of the set ones.
+///
+/// Compared to serializing the raw words—or worse, one boolean per atom—this keeps stored
+/// configurations readable, and proportional to the number of included atoms.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CompactMask {
+    len: usize,
+    indices: Vec<usize>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for BitMask {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        CompactMask {
+            len: self.len(),
+            indices: (0..self.len())
+                .filter(|&idx| self.get(idx) == Some(true))
+                .collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BitMask {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let CompactMask { len, indices } = CompactMask::deserialize(deserializer)?;
+        let mut mask = BitMask::new(len);
+        for idx in indices {
+            if idx >= len {
+                return Err(serde::de::Error::custom(format!(
+                    "the index ({idx}) is outside the defined range of the mask (..{len})"
+                )));
+            }
+            mask.set(idx, true);
+        }
+        Ok(mask)
+    }
+}
+
+impl From<Vec<bool>> for BitMask {
+    fn from(bools: Vec<bool>) -> Self {
+        Self::from_iter(bools)
+    }
+}
+
+impl FromIterator<bool> for BitMask {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut mask = Self::default();
+        for value in iter {
+            let idx = mask.len;
+            mask.len += 1;
+            if mask.words.len() * Self::BITS < mask.len {
+                mask.words.push(0);
+            }
+            if value {
+                mask.set(idx, true);
+            }
+        }
+        mask
+    }
+}
 
 // Invariant: The selection is only valid if the frame it reads them into is appropriately sized.
 // It is assumed that the frame is correctly sized, i.e.,
@@ -13,6 +178,7 @@ use std::num::NonZeroU64;
 // For Map a further invariant exists:
 //     len(Mask) <= len(encoded_atoms)
 /// A selection of atoms.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Default, Clone)]
 pub enum AtomSelection {
     /// Include all atoms.
@@ -22,12 +188,30 @@ pub enum AtomSelection {
     ///
     /// If the value of the mask at an index `n` is `true`, the position at that same index `n` is
     /// included in the selection.
-    Mask(Vec<bool>), // TODO: Bitmap optimization?
+    Mask(BitMask),
     /// Index of the position right after the last position to be included in the selection.
     ///
     /// This is an exclusive stop value, such that a value of 8 will mean that a total of 7 atoms
     /// are read into the frame.
     Until(u32),
+    /// Include positions that lie within a regular range, strided by `step`.
+    ///
+    /// The `start` is inclusive and the `end` is exclusive. A position at index `n` is included
+    /// if `start <= n < end` and `n - start` is a multiple of `step`.
+    ///
+    /// Contrary to a [`Mask`](AtomSelection::Mask) over the same indices, this variant does not
+    /// allocate, which makes it the preferred representation for regular strides such as "every
+    /// tenth atom".
+    Range {
+        /// The index of the first position to be included in the selection.
+        start: u32,
+        /// Index of the position right after the last position that may be included in the
+        /// selection. This is an exclusive stop value.
+        end: u32,
+        /// The stride between included positions. A `step` of one includes every position
+        /// between `start` and `end`.
+        step: NonZeroU32,
+    },
 }
 
 impl AtomSelection {
@@ -35,25 +219,65 @@ impl AtomSelection {
     pub fn from_index_list(indices: &[u32]) -> Self {
         let max = match indices.iter().max() {
             Some(&max) => max as usize + 1,
-            None => return Self::Mask(Vec::new()),
+            None => return Self::Mask(BitMask::default()),
         };
-        let mut mask = Vec::with_capacity(max);
-        mask.resize(max, false);
+        let mut mask = BitMask::new(max);
 
         for &idx in indices {
-            mask[idx as usize] = true;
+            mask.set(idx as usize, true);
         }
 
         Self::Mask(mask)
     }
 
+    /// Create a boolean mask from a set of contiguous index ranges.
+    ///
+    /// Residue-based selections are naturally a handful of contiguous ranges, and this sets the
+    /// covered positions directly rather than enumerating every index through
+    /// [`AtomSelection::from_index_list`]. The ranges may overlap and come in any order; the
+    /// resulting selection is their union.
+    pub fn from_ranges(ranges: &[std::ops::Range<u32>]) -> Self {
+        let len = ranges
+            .iter()
+            .filter(|range| !range.is_empty())
+            .map(|range| range.end as usize)
+            .max()
+            .unwrap_or(0);
+        let mut mask = BitMask::new(len);
+        for range in ranges {
+            for idx in range.clone() {
+                mask.set(idx as usize, true);
+            }
+        }
+        Self::Mask(mask)
+    }
+
+    /// Create a new `AtomSelection::Range` variant.
+    ///
+    /// The `start` defaults to zero and the `step` defaults to one. The `end` is an exclusive
+    /// bound.
+    ///
+    /// An instance where `start` > `end` is a valid selection, but it will not make much sense,
+    /// since it will be understood to select zero atoms. This case will trigger a `debug_assert`.
+    pub fn range(start: Option<u32>, end: u32, step: Option<NonZeroU32>) -> Self {
+        let start = start.unwrap_or(0);
+        let step = step.unwrap_or(NonZeroU32::MIN);
+
+        debug_assert!(
+            start <= end,
+            "the start of a selection ({start}) may not exceed the end ({end})"
+        );
+
+        Self::Range { start, end, step }
+    }
+
     /// Determine whether some index `idx` is included in this [`AtomSelection`].
     ///
     /// Will return [`None`] once the index is beyond the scope of this `AtomSelection`.
     pub fn is_included(&self, idx: usize) -> Option<bool> {
         match self {
             AtomSelection::All => Some(true),
-            AtomSelection::Mask(mask) => mask.get(idx).copied(),
+            AtomSelection::Mask(mask) => mask.get(idx),
             AtomSelection::Until(until) => {
                 if idx <= *until as usize {
                     Some(true)
@@ -61,6 +285,19 @@ impl AtomSelection {
                     None
                 }
             }
+            AtomSelection::Range { start, end, step } => {
+                // Determine whether `idx` is already beyond the defined range.
+                if *end as usize <= idx {
+                    return None;
+                }
+                let start = *start as usize;
+                let in_range = start <= idx;
+                // As in `Range::is_included`, the saturating sub is defensive. If the subtraction
+                // would overflow, `in_range` is already false.
+                let in_step =
+                    step.get() == 1 || idx.saturating_sub(start) % step.get() as usize == 0;
+                Some(in_range && in_step)
+            }
         }
     }
 
@@ -73,11 +310,22 @@ impl AtomSelection {
     pub fn last(&self) -> Option<usize> {
         match self {
             AtomSelection::All => None,
-            AtomSelection::Mask(mask) => match mask.iter().rposition(|&entry| entry) {
+            AtomSelection::Mask(mask) => match mask.last_set() {
                 Some(n) => Some(n + 1),
                 None => Some(0),
             },
             AtomSelection::Until(until) => Some(*until as usize),
+            AtomSelection::Range { start, end, step } => {
+                let length = end.saturating_sub(*start);
+                if length == 0 {
+                    return Some(0);
+                }
+                // The last index that is actually visited by the step, plus one. This mirrors the
+                // `Mask` convention above, where the value one past the last included index is
+                // returned.
+                let last = end - 1 - ((length - 1) % step.get());
+                Some(last as usize + 1)
+            }
         }
     }
 
@@ -87,12 +335,149 @@ impl AtomSelection {
     pub(crate) fn natoms_selected(&self, frame_natoms: usize) -> usize {
         match self {
             AtomSelection::All => frame_natoms,
-            AtomSelection::Mask(mask) => mask
-                .iter()
-                .take(frame_natoms)
-                .filter(|&&include| include)
-                .count(),
+            AtomSelection::Mask(mask) => mask.count_ones_below(frame_natoms),
             AtomSelection::Until(until) => usize::min(*until as usize, frame_natoms),
+            AtomSelection::Range { start, end, step } => {
+                let end = usize::min(*end as usize, frame_natoms);
+                let length = end.saturating_sub(*start as usize);
+                length.div_ceil(step.get() as usize)
+            }
+        }
+    }
+
+    /// Returns an iterator over the indices that are included in this [`AtomSelection`], for a
+    /// system of `natoms` positions.
+    ///
+    /// For `All` and `Until`, the implied range is enumerated up to `natoms`; for `Mask` and
+    /// `Range`, the included positions are yielded in order. No allocations are made.
+    pub fn iter_indices(&self, natoms: usize) -> impl Iterator<Item = u32> + '_ {
+        (0..natoms as u32).filter(move |&idx| self.is_included(idx as usize).unwrap_or(false))
+    }
+
+    /// Returns the list of indices that are included in this [`AtomSelection`], for a system of
+    /// `natoms` positions.
+    ///
+    /// This is the inverse of [`AtomSelection::from_index_list`], which can be useful for writing
+    /// out index groups or reporting a selection to the user.
+    pub fn to_index_list(&self, natoms: usize) -> Vec<u32> {
+        self.iter_indices(natoms).collect()
+    }
+
+    /// The index one past the last position that may be included in this [`AtomSelection`], if
+    /// the selection is bounded at all.
+    ///
+    /// Contrary to [`AtomSelection::last`], this accounts for the inclusive treatment of the
+    /// `Until` stop value in [`AtomSelection::is_included`].
+    fn bound(&self) -> Option<usize> {
+        match self {
+            AtomSelection::All => None,
+            AtomSelection::Until(until) => Some(*until as usize + 1),
+            AtomSelection::Mask(_) | AtomSelection::Range { .. } => self.last(),
+        }
+    }
+
+    /// Returns the union of this [`AtomSelection`] and `other`: a selection that includes every
+    /// position that is included in either.
+    ///
+    /// Unions with `All` remain `All`, and the union of two `Until` selections stays an `Until`.
+    /// Any other combination is materialized into a `Mask` that spans both selections.
+    pub fn union(&self, other: &Self) -> Self {
+        use AtomSelection as AS;
+        match (self, other) {
+            (AS::All, _) | (_, AS::All) => AS::All,
+            (AS::Until(a), AS::Until(b)) => AS::Until(*a.max(b)),
+            (a, b) => {
+                // Both selections are bounded here, since `All` is handled above.
+                let n = usize::max(a.bound().unwrap(), b.bound().unwrap());
+                AS::Mask(
+                    (0..n)
+                        .map(|idx| {
+                            a.is_included(idx).unwrap_or(false)
+                                || b.is_included(idx).unwrap_or(false)
+                        })
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    /// Returns the intersection of this [`AtomSelection`] and `other`: a selection that includes
+    /// every position that is included in both.
+    ///
+    /// Intersections with `All` yield the other selection, and the intersection of two `Until`
+    /// selections stays an `Until`. Any other combination is materialized into a `Mask`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        use AtomSelection as AS;
+        match (self, other) {
+            (AS::All, sel) | (sel, AS::All) => sel.clone(),
+            (AS::Until(a), AS::Until(b)) => AS::Until(*a.min(b)),
+            (a, b) => {
+                // Both selections are bounded here, since `All` is handled above.
+                let n = usize::min(a.bound().unwrap(), b.bound().unwrap());
+                AS::Mask(
+                    (0..n)
+                        .map(|idx| {
+                            a.is_included(idx).unwrap_or(false)
+                                && b.is_included(idx).unwrap_or(false)
+                        })
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    /// Returns the complement of this [`AtomSelection`] over a system of `natoms` positions: a
+    /// selection that includes exactly the positions this one does not.
+    ///
+    /// The total number of atoms must be passed in, since selections such as `All` and `Until`
+    /// imply inclusion for every index beyond any bound. The complement of `All` is an empty
+    /// selection, and the complement of an empty selection over `natoms` atoms includes all of
+    /// them.
+    pub fn complement(&self, natoms: usize) -> Self {
+        Self::Mask(
+            (0..natoms)
+                .map(|idx| !self.is_included(idx).unwrap_or(false))
+                .collect(),
+        )
+    }
+
+    /// Check that this [`AtomSelection`] fits a system of `natoms` positions.
+    ///
+    /// This enforces the invariant stated above: a `Mask` may not extend beyond the encoded
+    /// atoms—even where its trailing bits are unset—and the stop values of `Until` and `Range`
+    /// must lie within the system. A selection built for a larger system silently produces
+    /// undefined trailing positions when it is read against a smaller one, so validating it up
+    /// front turns that foot-gun into an error.
+    ///
+    /// # Errors
+    ///
+    /// If the selection may address a position at or beyond `natoms`, a [`SelectionError`]
+    /// carrying the offending sizes is returned.
+    pub fn validate(&self, natoms: usize) -> Result<(), SelectionError> {
+        let selection_bound = match self {
+            AtomSelection::All => 0,
+            AtomSelection::Mask(mask) => mask.len(),
+            AtomSelection::Until(until) => *until as usize + 1,
+            AtomSelection::Range { .. } => self.bound().unwrap_or(0),
+        };
+        if selection_bound > natoms {
+            return Err(SelectionError {
+                selection_bound,
+                natoms,
+            });
+        }
+        Ok(())
+    }
+
+    /// The index of the first position that is included in this [`AtomSelection`].
+    ///
+    /// For a selection that includes no positions at all, an index beyond any position is
+    /// returned, so that every index compares as lying before the start of the selection.
+    pub(crate) fn first_included(&self) -> usize {
+        match self {
+            AtomSelection::All | AtomSelection::Until(_) => 0,
+            AtomSelection::Mask(mask) => mask.first_set().unwrap_or(usize::MAX),
+            AtomSelection::Range { start, .. } => *start as usize,
         }
     }
 
@@ -112,7 +497,116 @@ impl AtomSelection {
     }
 }
 
+/// The error returned when an [`AtomSelection`] does not fit the system it is validated against.
+///
+/// Returned by [`AtomSelection::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectionError {
+    /// The index one past the last position the selection may address.
+    pub selection_bound: usize,
+    /// The number of atoms in the system the selection was validated against.
+    pub natoms: usize,
+}
+
+impl std::fmt::Display for SelectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the selection addresses positions up to index {}, but the system only holds {} atoms",
+            self.selection_bound, self.natoms
+        )
+    }
+}
+
+impl std::error::Error for SelectionError {}
+
+impl std::fmt::Display for AtomSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AtomSelection::All => write!(f, "all"),
+            AtomSelection::Mask(mask) => {
+                write!(f, "indices=")?;
+                let mut first = true;
+                for idx in (0..mask.len()).filter(|&idx| mask.get(idx) == Some(true)) {
+                    if !first {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{idx}")?;
+                    first = false;
+                }
+                Ok(())
+            }
+            AtomSelection::Until(until) => write!(f, "until={until}"),
+            AtomSelection::Range { start, end, step } => write!(f, "range={start}:{end}:{step}"),
+        }
+    }
+}
+
+impl std::str::FromStr for AtomSelection {
+    type Err = String;
+
+    /// Parse an [`AtomSelection`] from its textual form, the inverse of its `Display`.
+    ///
+    /// Accepted forms are `all`, `until=<n>` (or a bare `<n>`), `indices=<i>,<j>,...`, and
+    /// `range=<start>:<end>:<step>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "all" {
+            return Ok(Self::All);
+        }
+        if let Some(until) = s.strip_prefix("until=") {
+            let until = until
+                .parse()
+                .map_err(|err| format!("could not parse until value {until:?}: {err}"))?;
+            return Ok(Self::Until(until));
+        }
+        if let Some(indices) = s.strip_prefix("indices=") {
+            let indices = indices
+                .split(',')
+                .filter(|word| !word.is_empty())
+                .map(|word| {
+                    word.parse()
+                        .map_err(|err| format!("could not parse index {word:?}: {err}"))
+                })
+                .collect::<Result<Vec<u32>, _>>()?;
+            return Ok(Self::from_index_list(&indices));
+        }
+        if let Some(range) = s.strip_prefix("range=") {
+            let mut components = range.splitn(3, ':');
+            let start = match components.next() {
+                None | Some("") => None,
+                Some(word) => Some(
+                    word.parse()
+                        .map_err(|err| format!("could not parse range start {word:?}: {err}"))?,
+                ),
+            };
+            let end = match components.next() {
+                None | Some("") => {
+                    return Err(format!("the range in {s:?} must have a bounded end"))
+                }
+                Some(word) => word
+                    .parse()
+                    .map_err(|err| format!("could not parse range end {word:?}: {err}"))?,
+            };
+            let step = match components.next() {
+                None | Some("") => None,
+                Some(word) => Some(
+                    word.parse()
+                        .map_err(|err| format!("could not parse range step {word:?}: {err}"))?,
+                ),
+            };
+            return Ok(Self::range(start, end, step));
+        }
+
+        // A bare stop value, as accepted on the command line.
+        let until = s
+            .parse()
+            .map_err(|err| format!("could not parse atom selection {s:?}: {err}"))?;
+        Ok(Self::Until(until))
+    }
+}
+
 /// A selection of [`Frame`]s.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Default, Clone)]
 pub enum FrameSelection {
     /// Include all frames that are in a trajectory.
@@ -135,6 +629,124 @@ impl FrameSelection {
         Self::FrameList(BTreeSet::from_iter(iter))
     }
 
+    /// Create a [`FrameSelection`] that includes every `n`th frame of a trajectory, starting at
+    /// the first.
+    pub fn every_nth(n: NonZeroU64) -> Self {
+        Self::Range(Range::new(None, None, Some(n)))
+    }
+
+    /// Returns a [`FrameSelection`] that keeps every `step`th frame of the frames included in
+    /// this selection.
+    ///
+    /// For `All` and `Range`, the stride is applied symbolically, such that no allocation is
+    /// involved. For `FrameList`, every `step`th index of the (ordered) list is kept, which
+    /// preserves the uniqueness and ordering invariants of the variant.
+    pub fn downsample(&self, step: NonZeroU64) -> Self {
+        match self {
+            FrameSelection::All => Self::every_nth(step),
+            FrameSelection::Range(range) => FrameSelection::Range(Range::new(
+                Some(range.start),
+                range.end,
+                Some(range.step.saturating_mul(step)),
+            )),
+            FrameSelection::FrameList(list) => FrameSelection::FrameList(
+                list.iter().copied().step_by(step.get() as usize).collect(),
+            ),
+        }
+    }
+
+    /// Returns the tightest representation of this [`FrameSelection`] that includes the same
+    /// frames.
+    ///
+    /// A `FrameList` whose indices form a perfect arithmetic progression is collapsed into a
+    /// `Range`, which is cheaper to scan and makes [`FrameSelection::until`] trivial. Any other
+    /// selection is returned unchanged, preserving the `FrameList` invariant.
+    pub fn normalize(&self) -> FrameSelection {
+        let FrameSelection::FrameList(list) = self else {
+            return self.clone();
+        };
+        let mut indices = list.iter().copied();
+        let Some(first) = indices.next() else {
+            return self.clone();
+        };
+        let Some(second) = indices.next() else {
+            // A single index is a range of one.
+            return FrameSelection::Range(Range::new(
+                Some(first as u64),
+                Some(first as u64 + 1),
+                None,
+            ));
+        };
+        // The indices in the list are unique and ordered, so the differences are nonzero.
+        let step = second - first;
+        let mut last = second;
+        for idx in indices {
+            if idx - last != step {
+                return self.clone();
+            }
+            last = idx;
+        }
+        FrameSelection::Range(Range::new(
+            Some(first as u64),
+            Some(last as u64 + 1),
+            NonZeroU64::new(step as u64),
+        ))
+    }
+
+    /// Returns the union of two [`FrameSelection`]s as a single selection, if the union can be
+    /// represented exactly.
+    ///
+    /// When both selections are bounded, their union is materialized and normalized into the
+    /// tightest form, such that redundant overlapping selections collapse into one scan. A union
+    /// involving an unbounded selection is only representable when one selection subsumes the
+    /// other; if it does not, [`None`] is returned and the selections must be kept separate.
+    pub fn merge(a: &Self, b: &Self) -> Option<Self> {
+        match (a, b) {
+            (Self::All, _) | (_, Self::All) => Some(Self::All),
+            _ => match (a.until(), b.until()) {
+                // Both selections are bounded: materialize the union and collapse it.
+                (Some(a_until), Some(b_until)) => {
+                    let until = usize::max(a_until, b_until);
+                    let list = (0..until).filter(|&idx| {
+                        a.is_included(idx).unwrap_or(false) || b.is_included(idx).unwrap_or(false)
+                    });
+                    Some(Self::framelist_from_iter(list).normalize())
+                }
+                (None, Some(_)) => Self::subsumes(a, b).then(|| a.clone()),
+                (Some(_), None) => Self::subsumes(b, a).then(|| b.clone()),
+                (None, None) => {
+                    if Self::subsumes(a, b) {
+                        Some(a.clone())
+                    } else if Self::subsumes(b, a) {
+                        Some(b.clone())
+                    } else {
+                        None
+                    }
+                }
+            },
+        }
+    }
+
+    /// Returns whether every frame included in `inner` is also included in `outer`.
+    fn subsumes(outer: &Self, inner: &Self) -> bool {
+        match inner.until() {
+            Some(until) => (0..until).all(|idx| {
+                !inner.is_included(idx).unwrap_or(false) || outer.is_included(idx).unwrap_or(false)
+            }),
+            None => {
+                // `inner` is an unbounded range (`All` is handled by the caller), so `outer` can
+                // only subsume it if it is an unbounded range as well, with a compatible stride.
+                let (Self::Range(outer), Self::Range(inner)) = (outer, inner) else {
+                    return false;
+                };
+                outer.end.is_none()
+                    && inner.start >= outer.start
+                    && inner.step.get() % outer.step.get() == 0
+                    && (inner.start - outer.start) % outer.step.get() == 0
+            }
+        }
+    }
+
     /// Determine whether some index `idx` is included in this [`FrameSelection`].
     ///
     /// Will return [`None`] once the index is beyond the scope of this `FrameSelection`.
@@ -159,7 +771,13 @@ impl FrameSelection {
     pub fn until(&self) -> Option<usize> {
         match self {
             FrameSelection::All => None,
-            FrameSelection::Range(range) => range.last().map(|last| last + 1),
+            FrameSelection::Range(range) => {
+                // A start-beyond-end range selects nothing, so nothing needs to be read at all.
+                if range.end.is_some_and(|end| range.start >= end) {
+                    return Some(0);
+                }
+                range.last().map(|last| last + 1)
+            }
             FrameSelection::FrameList(list) => {
                 Some(list.iter().max().copied().unwrap_or_default() + 1)
             }
@@ -167,6 +785,53 @@ impl FrameSelection {
     }
 }
 
+impl std::fmt::Display for FrameSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameSelection::All => write!(f, "all"),
+            FrameSelection::Range(range) => write!(f, "{range}"),
+            FrameSelection::FrameList(list) => {
+                write!(f, "frames=")?;
+                let mut first = true;
+                for idx in list {
+                    if !first {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{idx}")?;
+                    first = false;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for FrameSelection {
+    type Err = String;
+
+    /// Parse a [`FrameSelection`] from its textual form, the inverse of its `Display`.
+    ///
+    /// Accepted forms are `all`, `frames=<i>,<j>,...`, and a range `<start>:<end>:<step>` where
+    /// each of the values is optional (see [`Range`]).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "all" {
+            return Ok(Self::All);
+        }
+        if let Some(frames) = s.strip_prefix("frames=") {
+            let indices = frames
+                .split(',')
+                .filter(|word| !word.is_empty())
+                .map(|word| {
+                    word.parse()
+                        .map_err(|err| format!("could not parse frame index {word:?}: {err}"))
+                })
+                .collect::<Result<Vec<usize>, _>>()?;
+            return Ok(Self::framelist_from_iter(indices));
+        }
+        Ok(Self::Range(s.parse()?))
+    }
+}
+
 /// A selection of [`Frame`](super::Frame)s to be read from an [`XTCReader`](super::XTCReader).
 ///
 /// The `start` of a [`Selection`] is always bounded, and is zero by default.
@@ -182,6 +847,7 @@ impl FrameSelection {
 /// An instance where `start` > `end` is a valid `Selection`, but it will not make much sense,
 /// since the `Selection` will be understood to produce zero steps. This case will trigger a
 /// `debug_assert`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Copy)]
 pub struct Range {
     /// The `start` of a [`Selection`] is always bounded, and is zero by default.
@@ -200,6 +866,8 @@ pub struct Range {
 }
 
 impl Range {
+    /// A range whose `start` lies at or past its `end` is valid, and selects nothing:
+    /// [`Range::is_included`] includes no index, and [`Range::last`] reports a zero length.
     pub fn new(start: Option<u64>, end: Option<u64>, step: Option<NonZeroU64>) -> Self {
         let mut sel = Self {
             end,
@@ -212,14 +880,6 @@ impl Range {
             sel.step = step;
         }
 
-        if let Some(end) = sel.end {
-            let start = sel.start;
-            debug_assert!(
-                start <= end,
-                "the start of a selection ({start}) may not exceed the end ({end})"
-            );
-        }
-
         sel
     }
 
@@ -247,7 +907,12 @@ impl Range {
     /// the last index before the `end`, taking the value of `step` into account.
     pub fn last(&self) -> Option<usize> {
         self.end.map(|end| {
-            let length = end.saturating_sub(self.start);
+            // A start at or past the end selects nothing. Report the zero length rather than an
+            // index computed from the end, which would suggest frames where there are none.
+            if self.start >= end {
+                return 0;
+            }
+            let length = end - self.start;
             let remainder = length % self.step;
             (end - remainder) as usize
         })
@@ -264,6 +929,85 @@ impl Default for Range {
     }
 }
 
+impl std::fmt::Display for Range {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:", self.start)?;
+        if let Some(end) = self.end {
+            write!(f, "{end}")?;
+        }
+        write!(f, ":{}", self.step)
+    }
+}
+
+impl std::str::FromStr for Range {
+    type Err = String;
+
+    /// Parse a [`Range`] from the format `start:end:step`, where each of the values is optional.
+    ///
+    /// For instance, `3:14` selects the 4th up to and including the 14th frames, and `:100:2`
+    /// selects every second frame from the first 100.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut components = s.splitn(3, ':');
+        let mut parse_component = |name: &str| -> Result<Option<u64>, String> {
+            match components.next() {
+                None | Some("") => Ok(None),
+                Some(word) => word
+                    .parse()
+                    .map(Some)
+                    .map_err(|err| format!("could not parse range {name} {word:?}: {err}")),
+            }
+        };
+        let start = parse_component("start")?;
+        let end = parse_component("end")?;
+        let step = parse_component("step")?
+            .map(|step| {
+                NonZeroU64::new(step).ok_or_else(|| "the range step must not be zero".to_string())
+            })
+            .transpose()?;
+        Ok(Range::new(start, end, step))
+    }
+}
+
+/// A combined selection of frames and atoms.
+///
+/// The two axes of a selection tend to travel together—"protein backbone, production frames" is
+/// one thing to a caller—and a `Query` names that pair as a single reusable value rather than two
+/// loose arguments. It is consumed by [`XTCReader::read`](crate::XTCReader::read).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Default, Clone)]
+pub struct Query {
+    /// The frames to include.
+    pub frames: FrameSelection,
+    /// The atoms to include, for each included frame.
+    pub atoms: AtomSelection,
+}
+
+impl Query {
+    /// Create a new [`Query`] that includes every frame and every atom.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a [`Query`] that includes every frame and every atom.
+    ///
+    /// This matches the default behavior of the plain reading functions.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Replace the [`AtomSelection`] of this [`Query`].
+    pub fn atoms(mut self, atoms: AtomSelection) -> Self {
+        self.atoms = atoms;
+        self
+    }
+
+    /// Replace the [`FrameSelection`] of this [`Query`].
+    pub fn frames(mut self, frames: FrameSelection) -> Self {
+        self.frames = frames;
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -325,6 +1069,26 @@ mod tests {
         }
 
         /// This test serves to replicate a degenerate case I found.
+        #[test]
+        fn start_beyond_end() {
+            // A range whose start lies past its end is valid, and selects nothing.
+            let range = Range::new(Some(50), Some(40), None);
+            assert_eq!(range.last(), Some(0));
+            for idx in 0..100 {
+                assert_ne!(range.is_included(idx), Some(true));
+            }
+
+            // Wrapped into a FrameSelection, nothing needs to be read at all.
+            let selection = FrameSelection::Range(range);
+            assert_eq!(selection.until(), Some(0));
+
+            // The same holds with a stride, which previously skewed the computed last index.
+            let range = Range::new(Some(50), Some(40), NonZeroU64::new(3));
+            assert_eq!(range.last(), Some(0));
+            assert_eq!(range.is_included(39), Some(false));
+            assert_eq!(range.is_included(40), None);
+        }
+
         #[test]
         fn range_clamped_step() {
             let end = 50;
@@ -343,6 +1107,131 @@ mod tests {
             }
         }
 
+        #[test]
+        fn downsample() {
+            let step = NonZeroU64::new(2).unwrap();
+
+            let all = FrameSelection::All.downsample(NonZeroU64::new(4).unwrap());
+            for idx in 0..100 {
+                assert_eq!(all.is_included(idx), Some(idx % 4 == 0));
+            }
+
+            // Downsampling a range multiplies its step.
+            let range = FrameSelection::Range(Range::new(
+                Some(25),
+                Some(50),
+                Some(NonZeroU64::new(3).unwrap()),
+            ))
+            .downsample(step);
+            let included = [25, 31, 37, 43, 49];
+            for idx in 0..50 {
+                assert_eq!(range.is_included(idx), Some(included.contains(&idx)));
+            }
+            assert_eq!(range.until(), Some(50));
+
+            // Downsampling a frame list keeps every `step`th of its ordered indices.
+            let list = FrameSelection::framelist_from_iter([2, 3, 5, 7, 11, 13])
+                .downsample(NonZeroU64::new(3).unwrap());
+            for idx in 0..8 {
+                assert_eq!(list.is_included(idx), Some([2, 7].contains(&idx)));
+            }
+            assert!(list.is_included(8).is_none());
+        }
+
+        /// A property test: normalization must preserve `is_included` for a diverse set of
+        /// lists, and collapse the arithmetic ones.
+        #[test]
+        fn normalize() {
+            let lists: [&[usize]; 7] = [
+                &[],
+                &[7],
+                &[3, 10],
+                &[5, 8, 11, 14],
+                &[0, 1, 2, 3],
+                &[1, 2, 4, 8],
+                &[0, 10, 20, 30, 41],
+            ];
+            for indices in lists {
+                let list = FrameSelection::framelist_from_iter(indices.iter().copied());
+                let normalized = list.normalize();
+                for idx in 0..100 {
+                    assert_eq!(
+                        normalized.is_included(idx).unwrap_or(false),
+                        list.is_included(idx).unwrap_or(false),
+                        "normalization of {indices:?} diverges at {idx}"
+                    );
+                }
+            }
+
+            // Perfect arithmetic progressions collapse into ranges; anything else is preserved.
+            let arithmetic = FrameSelection::framelist_from_iter([5, 8, 11, 14]).normalize();
+            assert!(matches!(arithmetic, FrameSelection::Range(_)));
+            assert_eq!(arithmetic.until(), Some(15));
+            assert!(matches!(
+                FrameSelection::framelist_from_iter([1, 2, 4, 8]).normalize(),
+                FrameSelection::FrameList(_)
+            ));
+            assert!(matches!(FrameSelection::All.normalize(), FrameSelection::All));
+        }
+
+        #[test]
+        fn merge() {
+            let included = |sel: &FrameSelection, idx| sel.is_included(idx).unwrap_or(false);
+
+            // Bounded unions collapse into the tightest single representation.
+            let a = FrameSelection::Range(Range::new(Some(0), Some(10), None));
+            let b = FrameSelection::Range(Range::new(Some(5), Some(20), None));
+            let merged = FrameSelection::merge(&a, &b).unwrap();
+            for idx in 0..40 {
+                assert_eq!(included(&merged, idx), included(&a, idx) || included(&b, idx));
+            }
+            assert!(matches!(merged, FrameSelection::Range(_)));
+            // Note the quirk of `Range::until` for unstepped ranges, cf. the `until` test below.
+            assert_eq!(merged.until(), Some(21));
+
+            // Adjacent lists fuse, and may even come out as a range.
+            let merged = FrameSelection::merge(
+                &FrameSelection::framelist_from_iter([2, 3, 5]),
+                &FrameSelection::framelist_from_iter([4]),
+            )
+            .unwrap();
+            assert!(matches!(merged, FrameSelection::Range(_)));
+            for idx in 0..10 {
+                assert_eq!(included(&merged, idx), (2..=5).contains(&idx));
+            }
+
+            // `All` absorbs everything.
+            assert!(matches!(
+                FrameSelection::merge(&FrameSelection::All, &a),
+                Some(FrameSelection::All)
+            ));
+
+            // An unbounded range subsumes compatible selections...
+            let every_other =
+                FrameSelection::Range(Range::new(None, None, Some(2.try_into().unwrap())));
+            let every_fourth =
+                FrameSelection::Range(Range::new(None, None, Some(4.try_into().unwrap())));
+            let merged = FrameSelection::merge(&every_other, &every_fourth).unwrap();
+            for idx in 0..100 {
+                assert_eq!(included(&merged, idx), idx % 2 == 0);
+            }
+            assert!(FrameSelection::merge(
+                &every_other,
+                &FrameSelection::framelist_from_iter([0, 4, 8]),
+            )
+            .is_some());
+
+            // ...but incompatible unbounded unions cannot be represented by one selection.
+            let every_third =
+                FrameSelection::Range(Range::new(None, None, Some(3.try_into().unwrap())));
+            assert!(FrameSelection::merge(&every_other, &every_third).is_none());
+            assert!(FrameSelection::merge(
+                &every_other,
+                &FrameSelection::framelist_from_iter([1]),
+            )
+            .is_none());
+        }
+
         #[test]
         fn until() {
             let n = 100;
@@ -366,17 +1255,85 @@ mod tests {
             assert_eq!(from_until_stepped.until(), Some(85));
             assert!(all.until().is_none());
         }
+
+        #[test]
+        fn text_roundtrip() {
+            let selections = [
+                FrameSelection::All,
+                FrameSelection::framelist_from_iter([2, 3, 5, 7, 11, 13]),
+                FrameSelection::Range(Range::new(None, None, None)),
+                FrameSelection::Range(Range::new(Some(25), Some(50), Some(3.try_into().unwrap()))),
+                FrameSelection::Range(Range::new(None, Some(100), Some(17.try_into().unwrap()))),
+                FrameSelection::Range(Range::new(Some(42), None, None)),
+            ];
+            for selection in selections {
+                let parsed: FrameSelection = selection.to_string().parse().unwrap();
+                for idx in 0..200 {
+                    assert_eq!(parsed.is_included(idx), selection.is_included(idx));
+                }
+            }
+
+            // The range form of the command line interface is also accepted.
+            let range: FrameSelection = "25:50:3".parse().unwrap();
+            let expected =
+                FrameSelection::Range(Range::new(Some(25), Some(50), Some(3.try_into().unwrap())));
+            for idx in 0..60 {
+                assert_eq!(range.is_included(idx), expected.is_included(idx));
+            }
+            assert!("frames=1,two,3".parse::<FrameSelection>().is_err());
+            assert!("1:2:0".parse::<FrameSelection>().is_err());
+        }
+    }
+
+    mod bitmask {
+        use super::BitMask;
+
+        #[test]
+        fn word_boundaries() {
+            let n = 150;
+            let mut mask = BitMask::new(n);
+            assert_eq!(mask.len(), n);
+            assert!(mask.last_set().is_none());
+            assert_eq!(mask.count_ones_below(n), 0);
+
+            // Set bits around the 64-bit word boundaries.
+            for idx in [0, 62, 63, 64, 65, 127, 128, 149] {
+                mask.set(idx, true);
+            }
+            for idx in 0..n {
+                let expected = [0, 62, 63, 64, 65, 127, 128, 149].contains(&idx);
+                assert_eq!(mask.get(idx), Some(expected));
+            }
+            assert!(mask.get(n).is_none());
+            assert_eq!(mask.last_set(), Some(149));
+            assert_eq!(mask.count_ones_below(n), 8);
+            assert_eq!(mask.count_ones_below(64), 3);
+            assert_eq!(mask.count_ones_below(65), 4);
+            assert_eq!(mask.count_ones_below(1000), 8);
+
+            mask.set(149, false);
+            assert_eq!(mask.last_set(), Some(128));
+            assert_eq!(mask.count_ones_below(n), 7);
+        }
+
+        #[test]
+        fn from_bools() {
+            let bools = vec![false, true, true, false, true];
+            let mask = BitMask::from(bools.clone());
+            assert_eq!(mask.len(), bools.len());
+            assert!(mask.iter().eq(bools.iter().copied()));
+        }
     }
 
     mod atom {
-        use super::AtomSelection;
+        use super::{AtomSelection, BitMask};
 
         #[test]
         fn zero_selection() {
             let m = 100;
 
-            let mask_empty = AtomSelection::Mask(vec![]);
-            let mask_false = AtomSelection::Mask(vec![false; m]);
+            let mask_empty = AtomSelection::Mask(vec![].into());
+            let mask_false = AtomSelection::Mask(vec![false; m].into());
             let list_empty = AtomSelection::from_index_list(&[]);
             let list_zero = AtomSelection::from_index_list(&[0]);
             let until_zero = AtomSelection::Until(0);
@@ -402,8 +1359,8 @@ mod tests {
         #[test]
         fn first_n() {
             let n = 100;
-            let mask = AtomSelection::Mask(vec![true; n]);
-            let mask_trailing_false = AtomSelection::Mask([vec![true; n], vec![false; n]].concat());
+            let mask = AtomSelection::Mask(vec![true; n].into());
+            let mask_trailing_false = AtomSelection::Mask([vec![true; n], vec![false; n]].concat().into());
             let list = AtomSelection::from_index_list(&(0..n as u32).collect::<Vec<_>>());
             let until = AtomSelection::Until(n as u32 - 1);
             let all = AtomSelection::All;
@@ -427,9 +1384,12 @@ mod tests {
         fn non_continuous_mask() {
             let n = 100;
 
-            let mask = AtomSelection::Mask(vec![
-                true, true, true, false, false, false, true, false, false, true, false,
-            ]);
+            let mask = AtomSelection::Mask(
+                vec![
+                    true, true, true, false, false, false, true, false, false, true, false,
+                ]
+                .into(),
+            );
             assert_eq!(mask.is_included(0), Some(true));
             assert_eq!(mask.is_included(1), Some(true));
             assert_eq!(mask.is_included(2), Some(true));
@@ -476,5 +1436,278 @@ mod tests {
             let limit = steps.reading_limit(n);
             assert_eq!(limit, 91);
         }
+
+        #[test]
+        fn first_included() {
+            assert_eq!(AtomSelection::All.first_included(), 0);
+            assert_eq!(AtomSelection::Until(10).first_included(), 0);
+            assert_eq!(
+                AtomSelection::range(Some(70), 90, None).first_included(),
+                70
+            );
+
+            let mut mask = BitMask::new(100);
+            assert_eq!(mask.first_set(), None);
+            assert_eq!(AtomSelection::Mask(mask.clone()).first_included(), usize::MAX);
+            mask.set(67, true); // Past the first word, to cross a word boundary.
+            mask.set(93, true);
+            assert_eq!(mask.first_set(), Some(67));
+            assert_eq!(AtomSelection::Mask(mask).first_included(), 67);
+        }
+
+        #[test]
+        fn range() {
+            let n = 100;
+            let step = 10.try_into().unwrap();
+
+            // A strided range should match its materialized mask counterpart.
+            let range = AtomSelection::range(None, n as u32, Some(step));
+            let list = AtomSelection::from_index_list(
+                Vec::from_iter((0..n as u32).step_by(step.get() as usize)).as_slice(),
+            );
+            for idx in 0..2 * n {
+                if idx < n {
+                    assert_eq!(range.is_included(idx), Some(idx % step.get() as usize == 0));
+                } else {
+                    assert!(range.is_included(idx).is_none());
+                }
+            }
+            assert_eq!(range.natoms_selected(n), list.natoms_selected(n));
+            assert_eq!(range.natoms_selected(n), 10);
+            assert_eq!(range.reading_limit(n), list.reading_limit(n));
+            assert_eq!(range.reading_limit(n), 91);
+
+            let offset = AtomSelection::range(Some(20), 50, None);
+            for idx in 0..60 {
+                if idx < 50 {
+                    assert_eq!(offset.is_included(idx), Some(idx >= 20));
+                } else {
+                    assert!(offset.is_included(idx).is_none());
+                }
+            }
+            assert_eq!(offset.natoms_selected(n), 30);
+            assert_eq!(offset.reading_limit(n), 50);
+        }
+
+        #[test]
+        fn from_ranges() {
+            let n = 100;
+
+            // Out-of-order and overlapping ranges take union semantics, and must match the
+            // equivalent index list in every regard.
+            let ranges = AtomSelection::from_ranges(&[20..30, 0..5, 3..8, 27..35]);
+            let indices: Vec<u32> = (0..8).chain(20..35).collect();
+            let list = AtomSelection::from_index_list(&indices);
+            for idx in 0..2 * n {
+                assert_eq!(ranges.is_included(idx), list.is_included(idx));
+            }
+            assert_eq!(ranges.natoms_selected(n), list.natoms_selected(n));
+            assert_eq!(ranges.natoms_selected(n), indices.len());
+            assert_eq!(ranges.reading_limit(n), list.reading_limit(n));
+            assert_eq!(ranges.reading_limit(n), 35);
+
+            // Empty ranges contribute nothing; the reversed one is deliberate.
+            #[allow(clippy::reversed_empty_ranges)]
+            let empty = AtomSelection::from_ranges(&[5..5, 7..3]);
+            assert!(empty.is_included(0).is_none());
+            assert_eq!(empty.natoms_selected(n), 0);
+        }
+
+        #[test]
+        fn index_list_roundtrip() {
+            let n = 100;
+
+            let indices = [3u32, 5, 8, 13, 21, 34, 55, 89];
+            let list = AtomSelection::from_index_list(&indices);
+            assert_eq!(list.to_index_list(n), indices);
+            assert!(list.iter_indices(n).eq(indices.iter().copied()));
+            // Truncation to the system size applies.
+            assert_eq!(list.to_index_list(30), [3, 5, 8, 13, 21]);
+
+            // Going from a mask to an index list and back must be idempotent.
+            let mask = AtomSelection::Mask(Vec::from_iter((0..80).map(|idx| idx % 3 == 0)).into());
+            let roundtripped = AtomSelection::from_index_list(&mask.to_index_list(n));
+            for idx in 0..2 * n {
+                assert_eq!(
+                    mask.is_included(idx).unwrap_or(false),
+                    roundtripped.is_included(idx).unwrap_or(false),
+                );
+            }
+
+            // For `All` and `Until`, the implied range is enumerated.
+            assert!(AtomSelection::All.iter_indices(n).eq(0..n as u32));
+            assert_eq!(AtomSelection::Until(5).to_index_list(n), [0, 1, 2, 3, 4, 5]);
+        }
+
+        /// A property test: the set operations must agree with `is_included` at every index, for
+        /// every pairing of a diverse set of selections.
+        #[test]
+        fn set_operations() {
+            let n = 100;
+            let selections = [
+                AtomSelection::All,
+                AtomSelection::Until(0),
+                AtomSelection::Until(35),
+                AtomSelection::range(Some(10), 60, Some(7.try_into().unwrap())),
+                AtomSelection::range(None, 90, None),
+                AtomSelection::from_index_list(&[]),
+                AtomSelection::from_index_list(&[3, 5, 8, 13, 21, 34, 55, 89]),
+                AtomSelection::Mask(Vec::from_iter((0..80).map(|idx| idx % 3 == 0)).into()),
+            ];
+
+            let included = |sel: &AtomSelection, idx: usize| sel.is_included(idx).unwrap_or(false);
+            for a in &selections {
+                for b in &selections {
+                    let union = a.union(b);
+                    let intersection = a.intersection(b);
+                    for idx in 0..2 * n {
+                        assert_eq!(
+                            included(&union, idx),
+                            included(a, idx) || included(b, idx),
+                            "union of {a:?} and {b:?} diverges at {idx}"
+                        );
+                        assert_eq!(
+                            included(&intersection, idx),
+                            included(a, idx) && included(b, idx),
+                            "intersection of {a:?} and {b:?} diverges at {idx}"
+                        );
+                    }
+                }
+
+                let complement = a.complement(n);
+                for idx in 0..n {
+                    assert_eq!(
+                        included(&complement, idx),
+                        !included(a, idx),
+                        "complement of {a:?} diverges at {idx}"
+                    );
+                }
+                // Beyond the complemented range, nothing is included.
+                assert!(!included(&complement, n + 1));
+            }
+        }
+
+        /// The atom counterpart to the degenerate case covered by `frame::range_clamped_step`.
+        #[test]
+        fn range_clamped_step() {
+            let end = 50;
+            let s = AtomSelection::range(Some(25), end, Some(3.try_into().unwrap()));
+
+            let included = [25, 28, 31, 34, 37, 40, 43, 46, 49];
+            for i in 0..60u32 {
+                let expected = if i < end {
+                    Some(included.contains(&i))
+                } else {
+                    None
+                };
+                assert_eq!(s.is_included(i as usize), expected);
+            }
+            assert_eq!(s.last(), Some(50));
+            assert_eq!(s.natoms_selected(100), included.len());
+            assert_eq!(s.reading_limit(100), 50);
+        }
+
+        #[test]
+        fn validate() {
+            let n = 100;
+            assert!(AtomSelection::All.validate(0).is_ok());
+            assert!(AtomSelection::Until(n as u32 - 1).validate(n).is_ok());
+            assert!(AtomSelection::Mask(vec![false; n].into()).validate(n).is_ok());
+            assert!(AtomSelection::range(None, n as u32, None).validate(n).is_ok());
+
+            // A mask built for a larger system is rejected, even where its trailing bits are
+            // unset.
+            let err = AtomSelection::Mask(vec![false; n + 1].into())
+                .validate(n)
+                .unwrap_err();
+            assert_eq!(err.selection_bound, n + 1);
+            assert_eq!(err.natoms, n);
+            // `Until` is an inclusive stop value, so its bound lies one past it.
+            assert!(AtomSelection::Until(n as u32).validate(n).is_err());
+            // A strided range is bounded by the last index its step actually visits.
+            let strided = AtomSelection::range(Some(25), n as u32 + 20, Some(3.try_into().unwrap()));
+            assert!(strided.validate(n).is_err());
+            assert!(strided.validate(119).is_ok());
+        }
+
+        #[test]
+        fn text_roundtrip() {
+            let selections = [
+                AtomSelection::All,
+                AtomSelection::Until(42),
+                AtomSelection::from_index_list(&[2, 3, 5, 7, 11, 13]),
+                AtomSelection::from_index_list(&[]),
+                AtomSelection::range(Some(25), 50, Some(3.try_into().unwrap())),
+                AtomSelection::range(None, 100, None),
+            ];
+            for selection in selections {
+                let parsed: AtomSelection = selection.to_string().parse().unwrap();
+                for idx in 0..200 {
+                    assert_eq!(parsed.is_included(idx), selection.is_included(idx));
+                }
+            }
+
+            // The bare number form of the command line interface is also accepted.
+            assert_eq!("42".parse::<AtomSelection>().unwrap().last(), Some(42));
+            assert!("indices=1,two,3".parse::<AtomSelection>().is_err());
+            assert!("range=1:2:0".parse::<AtomSelection>().is_err());
+            assert!("range=1::3".parse::<AtomSelection>().is_err());
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    mod serde {
+        use super::{AtomSelection, FrameSelection, Range};
+
+        /// A round trip through JSON must reproduce identical `is_included` behavior, for both
+        /// selection flavors.
+        #[test]
+        fn json_roundtrip() {
+            let atoms = [
+                AtomSelection::All,
+                AtomSelection::Until(42),
+                AtomSelection::from_index_list(&[2, 3, 5, 7, 11, 13]),
+                AtomSelection::from_index_list(&[]),
+                AtomSelection::range(Some(25), 50, Some(3.try_into().unwrap())),
+            ];
+            for selection in atoms {
+                let json = serde_json::to_string(&selection).unwrap();
+                let parsed: AtomSelection = serde_json::from_str(&json).unwrap();
+                for idx in 0..200 {
+                    assert_eq!(
+                        parsed.is_included(idx),
+                        selection.is_included(idx),
+                        "roundtrip of {selection:?} through {json} diverges at {idx}"
+                    );
+                }
+            }
+
+            let frames = [
+                FrameSelection::All,
+                FrameSelection::framelist_from_iter([2, 3, 5, 7, 11, 13]),
+                FrameSelection::Range(Range::new(Some(25), Some(50), Some(3.try_into().unwrap()))),
+                FrameSelection::Range(Range::new(Some(42), None, None)),
+            ];
+            for selection in frames {
+                let json = serde_json::to_string(&selection).unwrap();
+                let parsed: FrameSelection = serde_json::from_str(&json).unwrap();
+                for idx in 0..200 {
+                    assert_eq!(parsed.is_included(idx), selection.is_included(idx));
+                }
+            }
+        }
+
+        /// The `Mask` variant serializes in its compact index-list form, not as one value per
+        /// atom, and a mask that overflows its own length is rejected on the way back in.
+        #[test]
+        fn compact_mask_form() {
+            let mask = AtomSelection::from_index_list(&[3, 900]);
+            let json = serde_json::to_string(&mask).unwrap();
+            assert_eq!(json, r#"{"Mask":{"len":901,"indices":[3,900]}}"#);
+            assert!(
+                serde_json::from_str::<AtomSelection>(r#"{"Mask":{"len":4,"indices":[9]}}"#)
+                    .is_err()
+            );
+        }
     }
 }
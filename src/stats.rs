@@ -0,0 +1,162 @@
+//! Streaming statistics over trajectories.
+//!
+//! A per-atom fluctuation analysis needs the mean and variance of each atom's position over a
+//! whole trajectory, but holding every frame in memory to compute them defeats the point of a
+//! streaming reader. The [`Accumulator`] ingests one [`Frame`] at a time and maintains running
+//! moments through Welford's online algorithm, which stays numerically stable over arbitrarily
+//! long trajectories.
+
+use std::io;
+
+use crate::Frame;
+
+/// A running mean and variance of per-atom positions, fed one [`Frame`] at a time.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// let mut reader = molly::XTCReader::open("trajectory.xtc")?;
+/// let mut frame = molly::Frame::default();
+/// let mut accumulator = molly::stats::Accumulator::new();
+/// while reader.read_frame_into(&mut frame)? {
+///     accumulator.accumulate(&frame)?;
+/// }
+/// let fluctuations = accumulator.variance();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Accumulator {
+    /// The number of frames that have been accumulated.
+    count: usize,
+    /// The running per-component means, kept in f64 for numerical headroom.
+    mean: Vec<f64>,
+    /// The running per-component sums of squared deviations from the mean.
+    m2: Vec<f64>,
+}
+
+impl Accumulator {
+    /// Create a new, empty [`Accumulator`].
+    ///
+    /// The number of atoms is taken from the first accumulated frame.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fold the positions of `frame` into the running statistics.
+    ///
+    /// # Errors
+    ///
+    /// If `frame` holds a different number of atoms than the frames accumulated before it, an
+    /// error carrying both counts is returned and the statistics are left untouched.
+    pub fn accumulate(&mut self, frame: &Frame) -> io::Result<()> {
+        if self.count == 0 {
+            self.mean = vec![0.0; frame.positions.len()];
+            self.m2 = vec![0.0; frame.positions.len()];
+        } else if frame.positions.len() != self.mean.len() {
+            return Err(io::Error::other(format!(
+                "the frame holds {} atoms, where the frames before it held {}",
+                frame.positions.len() / 3,
+                self.mean.len() / 3
+            )));
+        }
+
+        self.count += 1;
+        for (idx, &value) in frame.positions.iter().enumerate() {
+            let value = value as f64;
+            let delta = value - self.mean[idx];
+            self.mean[idx] += delta / self.count as f64;
+            self.m2[idx] += delta * (value - self.mean[idx]);
+        }
+        Ok(())
+    }
+
+    /// Returns the number of frames that have been accumulated.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the per-atom mean positions over the accumulated frames.
+    pub fn mean(&self) -> Vec<[f32; 3]> {
+        self.mean
+            .chunks_exact(3)
+            .map(|xyz| [xyz[0] as f32, xyz[1] as f32, xyz[2] as f32])
+            .collect()
+    }
+
+    /// Returns the per-atom position variances over the accumulated frames.
+    ///
+    /// This is the population variance, the mean squared deviation from the mean position.
+    pub fn variance(&self) -> Vec<[f32; 3]> {
+        if self.count == 0 {
+            return Vec::new();
+        }
+        self.m2
+            .chunks_exact(3)
+            .map(|xyz| {
+                [
+                    (xyz[0] / self.count as f64) as f32,
+                    (xyz[1] / self.count as f64) as f32,
+                    (xyz[2] / self.count as f64) as f32,
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_batch_computation() -> io::Result<()> {
+        let natoms = 5;
+        let frames: Vec<Frame> = (0..4)
+            .map(|fidx| Frame {
+                step: fidx,
+                positions: (0..3 * natoms)
+                    .map(|v| ((v * 7 + fidx as usize * 13) % 11) as f32 * 0.25)
+                    .collect(),
+                ..Frame::default()
+            })
+            .collect();
+
+        let mut accumulator = Accumulator::new();
+        for frame in &frames {
+            accumulator.accumulate(frame)?;
+        }
+        assert_eq!(accumulator.count(), frames.len());
+
+        // The streaming moments must match a plain batch computation.
+        let mean = accumulator.mean();
+        let variance = accumulator.variance();
+        assert_eq!(mean.len(), natoms);
+        assert_eq!(variance.len(), natoms);
+        for atom in 0..natoms {
+            for dim in 0..3 {
+                let values: Vec<f32> = frames
+                    .iter()
+                    .map(|frame| frame.positions[atom * 3 + dim])
+                    .collect();
+                let batch_mean = values.iter().sum::<f32>() / values.len() as f32;
+                let batch_variance = values
+                    .iter()
+                    .map(|value| (value - batch_mean).powi(2))
+                    .sum::<f32>()
+                    / values.len() as f32;
+                assert!((mean[atom][dim] - batch_mean).abs() < 1e-6);
+                assert!((variance[atom][dim] - batch_variance).abs() < 1e-6);
+            }
+        }
+
+        // A frame with a different atom count is rejected and leaves the statistics untouched.
+        let mismatched = Frame {
+            positions: vec![0.0; 3 * (natoms + 1)],
+            ..Frame::default()
+        };
+        assert!(accumulator.accumulate(&mismatched).is_err());
+        assert_eq!(accumulator.count(), frames.len());
+        assert_eq!(accumulator.mean(), mean);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,267 @@
+//! Reading trajectories from non-seekable streams.
+//!
+//! [`XTCReader`] leans on [`Seek`](std::io::Seek) to skip over unselected frames, which rules out
+//! sources like a pipe from `gzip -dc traj.xtc.gz` or stdin. [`XTCStreamReader`] covers those by
+//! never seeking: unselected frames are read and discarded instead of skipped over. Operations
+//! that genuinely need random access, like
+//! [`seek_to_frame`](crate::XTCReader::seek_to_frame), simply do not exist on this type.
+
+use std::io::{self, Read};
+
+use crate::selection::{AtomSelection, FrameSelection};
+use crate::{Frame, XTCReader};
+
+/// An xtc reader over a non-seekable stream.
+///
+/// Frames are consumed strictly front to back. A [`FrameSelection`] is honored by decoding the
+/// selected frames and discarding the bytes of the unselected ones, so forward-only `Range` and
+/// `FrameList` selections work as they do on a file. An [`AtomSelection`] is honored as usual.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// // Read a trajectory that is piped in over stdin.
+/// let mut reader = molly::XTCStreamReader::new(std::io::stdin().lock());
+/// for frame in reader.frames(&molly::selection::FrameSelection::All, &Default::default()) {
+///     let frame = frame?;
+///     // Do something with the frame.
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct XTCStreamReader<R> {
+    reader: XTCReader<R>,
+    /// The index within the stream of the next frame.
+    frame_idx: usize,
+}
+
+impl<R: Read> XTCStreamReader<R> {
+    pub fn new(stream: R) -> Self {
+        Self {
+            reader: XTCReader::new(stream),
+            frame_idx: 0,
+        }
+    }
+
+    /// Returns the index within the stream of the next frame.
+    pub fn frame_idx(&self) -> usize {
+        self.frame_idx
+    }
+
+    /// Reads and returns a [`Frame`] and advances one step.
+    ///
+    /// See [`XTCReader::read_frame`].
+    pub fn read_frame(&mut self, frame: &mut Frame) -> io::Result<()> {
+        self.read_frame_with_selection(frame, &AtomSelection::All)
+    }
+
+    /// Reads and returns a [`Frame`] according to the [`AtomSelection`], and advances one step.
+    ///
+    /// See [`XTCReader::read_frame_with_selection`].
+    pub fn read_frame_with_selection(
+        &mut self,
+        frame: &mut Frame,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<()> {
+        self.reader.read_frame_with_selection(frame, atom_selection)?;
+        self.frame_idx += 1;
+        Ok(())
+    }
+
+    /// Read the next frame into `frame`, reusing its allocations.
+    ///
+    /// See [`XTCReader::read_frame_into`].
+    pub fn read_frame_into(&mut self, frame: &mut Frame) -> io::Result<bool> {
+        self.read_frame_into_with_selection(frame, &AtomSelection::All)
+    }
+
+    /// Read the next frame into `frame` according to an [`AtomSelection`], reusing its
+    /// allocations.
+    ///
+    /// See [`XTCReader::read_frame_into_with_selection`].
+    pub fn read_frame_into_with_selection(
+        &mut self,
+        frame: &mut Frame,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<bool> {
+        let read = self
+            .reader
+            .read_frame_into_with_selection(frame, atom_selection)?;
+        if read {
+            self.frame_idx += 1;
+        }
+        Ok(read)
+    }
+
+    /// Consume the next frame without decoding its positions.
+    ///
+    /// The frame's bytes are read and discarded, which is how a stream reader "skips": no seeking
+    /// is involved. Returns `false` once the end of the stream is reached.
+    ///
+    /// # Errors
+    ///
+    /// This function will pass through any reader errors. A stream that ends partway through a
+    /// frame is an error, not a clean end of stream.
+    pub fn skip_frame(&mut self) -> io::Result<bool> {
+        let skipped = self.reader.skip_frame()?;
+        if skipped {
+            self.frame_idx += 1;
+        }
+        Ok(skipped)
+    }
+
+    /// Returns an iterator over the frames of this stream, decoding the frames selected by
+    /// `frame_selection` and discarding the rest.
+    ///
+    /// The iterator ends at the end of the stream, or as soon as the selection can include no
+    /// further frames, whichever comes first.
+    pub fn frames<'r>(
+        &'r mut self,
+        frame_selection: &'r FrameSelection,
+        atom_selection: &'r AtomSelection,
+    ) -> StreamFrames<'r, R> {
+        StreamFrames {
+            stream: self,
+            frame_selection,
+            atom_selection,
+            done: false,
+        }
+    }
+}
+
+/// Read and throw away `n` bytes from `reader`.
+pub(crate) fn discard_bytes<R: Read>(reader: &mut R, n: u64) -> io::Result<()> {
+    let discarded = io::copy(&mut reader.take(n), &mut io::sink())?;
+    if discarded < n {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("the stream ended {} bytes into a frame", n - discarded),
+        ));
+    }
+    Ok(())
+}
+
+/// An iterator over the selected frames of a stream, created by [`XTCStreamReader::frames`].
+///
+/// Yields `io::Result<Frame>`, such that decode errors are propagated to the caller rather than
+/// silently terminating the iteration.
+pub struct StreamFrames<'r, R> {
+    stream: &'r mut XTCStreamReader<R>,
+    frame_selection: &'r FrameSelection,
+    atom_selection: &'r AtomSelection,
+    done: bool,
+}
+
+impl<R: Read> Iterator for StreamFrames<'_, R> {
+    type Item = io::Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            match self.frame_selection.is_included(self.stream.frame_idx) {
+                // The selection holds no frames beyond this point.
+                None => self.done = true,
+                Some(false) => match self.stream.skip_frame() {
+                    Ok(true) => {}
+                    Ok(false) => self.done = true,
+                    Err(err) => {
+                        self.done = true;
+                        return Some(Err(err));
+                    }
+                },
+                Some(true) => {
+                    let mut frame = Frame::default();
+                    return match self
+                        .stream
+                        .read_frame_with_selection(&mut frame, self.atom_selection)
+                    {
+                        Ok(()) => Some(Ok(frame)),
+                        // We have found the end of the stream. No more frames, we're done.
+                        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                            self.done = true;
+                            None
+                        }
+                        Err(err) => {
+                            self.done = true;
+                            Some(Err(err))
+                        }
+                    };
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::selection::Range;
+    use crate::writer::XTCWriter;
+
+    /// A trajectory in memory, behind a `Read`-only wrapper so no accidental seeking can occur.
+    struct NoSeek<'a>(&'a [u8]);
+
+    impl Read for NoSeek<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    fn write_trajectory(nframes: u32) -> io::Result<Vec<u8>> {
+        let mut writer = XTCWriter::new(io::Cursor::new(Vec::new()));
+        for step in 0..nframes {
+            writer.write_frame(&Frame {
+                step,
+                time: step as f32,
+                precision: 1000.0,
+                positions: (0..3 * 30).map(|v| v as f32 * 0.01).collect(),
+                ..Frame::default()
+            })?;
+        }
+        Ok(writer.file.into_inner())
+    }
+
+    #[test]
+    fn selections_without_seeking() -> io::Result<()> {
+        let bytes = write_trajectory(10)?;
+
+        // Every third frame from index 2 up to 9, discarding the rest.
+        let mut stream = XTCStreamReader::new(NoSeek(&bytes));
+        let selection = FrameSelection::Range(Range::new(
+            Some(2),
+            Some(9),
+            Some(3.try_into().unwrap()),
+        ));
+        let steps: Vec<u32> = stream
+            .frames(&selection, &AtomSelection::All)
+            .map(|frame| Ok(frame?.step))
+            .collect::<io::Result<_>>()?;
+        assert_eq!(steps, [2, 5, 8]);
+
+        // An atom selection is honored as usual.
+        let mut stream = XTCStreamReader::new(NoSeek(&bytes));
+        let selection = FrameSelection::framelist_from_iter([0, 4]);
+        for frame in stream.frames(&selection, &AtomSelection::Until(10)) {
+            assert_eq!(frame?.natoms(), 10);
+        }
+        assert_eq!(stream.frame_idx(), 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn skip_to_end() -> io::Result<()> {
+        let bytes = write_trajectory(3)?;
+        let mut stream = XTCStreamReader::new(NoSeek(&bytes));
+        assert!(stream.skip_frame()?);
+        assert!(stream.skip_frame()?);
+        assert!(stream.skip_frame()?);
+        assert!(!stream.skip_frame()?);
+
+        // A stream that is cut off partway through a frame is an error, not a clean end.
+        let mut stream = XTCStreamReader::new(NoSeek(&bytes[..bytes.len() / 2]));
+        assert!(stream.skip_frame()?);
+        assert!(stream.skip_frame().is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+//! Shared fixture helpers for the unit tests.
+//!
+//! The unit tests across this crate write small synthetic trajectories to the system temp
+//! directory and read them back. The helpers here keep that boilerplate in one place, and tie
+//! the removal of the temporary files to a drop guard, so that a test failing partway through
+//! cannot leak its file.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::writer::XTCWriter;
+use crate::Frame;
+
+/// A path in the system temp directory whose file is removed when the guard is dropped.
+pub(crate) struct TempPath(PathBuf);
+
+impl TempPath {
+    /// Create a path for `name`, unique to this test process.
+    pub(crate) fn new(name: &str) -> Self {
+        Self(std::env::temp_dir().join(format!("molly_{name}_{}.xtc", std::process::id())))
+    }
+
+    /// Adopt a path created elsewhere, tying the removal of its file to this guard.
+    pub(crate) fn adopt(path: PathBuf) -> Self {
+        Self(path)
+    }
+}
+
+impl std::ops::Deref for TempPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        // The file may never have been written, and a failing test must not panic again here.
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// A deterministic compressed test frame over `natoms` atoms.
+///
+/// The positions follow a fixed 0.01-spaced pattern offset by the step, so consecutive frames
+/// are distinguishable and any read can be verified against a re-read.
+pub(crate) fn test_frame(step: u32, time: f32, natoms: usize) -> Frame {
+    Frame {
+        step,
+        time,
+        precision: 1000.0,
+        positions: (0..3 * natoms)
+            .map(|v| (v + step as usize) as f32 * 0.01)
+            .collect(),
+        ..Frame::default()
+    }
+}
+
+/// Write `frames` to a fresh [`TempPath`] named `name` and return the guarded path.
+pub(crate) fn write_trajectory(
+    name: &str,
+    frames: impl IntoIterator<Item = Frame>,
+) -> io::Result<TempPath> {
+    let path = TempPath::new(name);
+    let mut writer = XTCWriter::create(&path)?;
+    for frame in frames {
+        writer.write_frame(&frame)?;
+    }
+    Ok(path)
+}
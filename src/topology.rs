@@ -0,0 +1,199 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+use crate::selection::AtomSelection;
+
+/// The names and residues of the atoms in a system, read from a GROMACS `.gro` file.
+///
+/// Building an [`AtomSelection`] by hand means figuring out integer indices, where the natural
+/// description of a selection is "all CA atoms" or "residues 10 through 50". A [`Topology`] holds
+/// the atom name, residue name, and residue id for every atom index, and offers selection
+/// builders over them.
+///
+/// The `.gro` parser is deliberately minimal: a title line, a natoms line, one fixed-width line
+/// per atom, and the box footer. Velocities and positions are ignored, since the coordinates come
+/// from the trajectory.
+#[derive(Debug, Default, Clone)]
+pub struct Topology {
+    /// The name of each atom, in order.
+    atom_names: Vec<String>,
+    /// The residue name of each atom.
+    residue_names: Vec<String>,
+    /// The residue id of each atom, in the 1-based `.gro` convention.
+    residue_ids: Vec<u32>,
+}
+
+impl Topology {
+    /// Parse a [`Topology`] from a GROMACS `.gro` file.
+    ///
+    /// Only the first three fixed-width fields of each atom line are read: the residue id
+    /// (columns 0–4), the residue name (5–9), and the atom name (10–14). The number of atom lines
+    /// is taken from the natoms line, and the box footer is left alone.
+    ///
+    /// # Errors
+    ///
+    /// Next to passing through any reader errors, this function will return an error if the file
+    /// ends before the declared number of atoms, and for lines too short to hold the fields.
+    pub fn from_gro<R: Read>(reader: R) -> io::Result<Self> {
+        let mut lines = BufReader::new(reader).lines();
+        let mut next_line = |what: &str| {
+            lines
+                .next()
+                .transpose()?
+                .ok_or_else(|| io::Error::other(format!("gro file ends before {what}")))
+        };
+
+        let _title = next_line("its title line")?;
+        let natoms_line = next_line("its natoms line")?;
+        let natoms: usize = natoms_line.trim().parse().map_err(|err| {
+            io::Error::other(format!(
+                "could not parse gro natoms line {natoms_line:?}: {err}"
+            ))
+        })?;
+
+        let mut topology = Self::default();
+        for idx in 0..natoms {
+            let line = next_line("the declared number of atoms")?;
+            let (Some(residue_id), Some(residue_name), Some(atom_name)) =
+                (line.get(0..5), line.get(5..10), line.get(10..15))
+            else {
+                return Err(io::Error::other(format!(
+                    "gro atom line {} is too short to hold its fixed-width fields: {line:?}",
+                    idx + 1
+                )));
+            };
+            let residue_id: u32 = residue_id.trim().parse().map_err(|err| {
+                io::Error::other(format!(
+                    "could not parse gro residue id {residue_id:?}: {err}"
+                ))
+            })?;
+            topology.residue_ids.push(residue_id);
+            topology.residue_names.push(residue_name.trim().to_string());
+            topology.atom_names.push(atom_name.trim().to_string());
+        }
+
+        Ok(topology)
+    }
+
+    /// Parse the `.gro` file at the provided path.
+    ///
+    /// See [`Topology::from_gro`].
+    pub fn from_gro_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::from_gro(File::open(path)?)
+    }
+
+    /// Returns the number of atoms in this [`Topology`].
+    pub fn natoms(&self) -> usize {
+        self.atom_names.len()
+    }
+
+    /// Returns the atom name at `idx`, or [`None`] if `idx` lies beyond the system.
+    pub fn atom_name(&self, idx: usize) -> Option<&str> {
+        self.atom_names.get(idx).map(String::as_str)
+    }
+
+    /// Returns the residue name at `idx`, or [`None`] if `idx` lies beyond the system.
+    pub fn residue_name(&self, idx: usize) -> Option<&str> {
+        self.residue_names.get(idx).map(String::as_str)
+    }
+
+    /// Returns the residue id at `idx`, or [`None`] if `idx` lies beyond the system.
+    pub fn residue_id(&self, idx: usize) -> Option<u32> {
+        self.residue_ids.get(idx).copied()
+    }
+
+    /// Build an [`AtomSelection`] of the atoms for which `pred` holds.
+    ///
+    /// The predicate receives the atom name, the residue name, and the residue id. The named
+    /// builders below cover the common cases; this is the escape hatch for compound conditions
+    /// such as "CA atoms of residues named ALA".
+    pub fn select_where(&self, mut pred: impl FnMut(&str, &str, u32) -> bool) -> AtomSelection {
+        AtomSelection::Mask(
+            (0..self.natoms())
+                .map(|idx| {
+                    pred(
+                        &self.atom_names[idx],
+                        &self.residue_names[idx],
+                        self.residue_ids[idx],
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// Build an [`AtomSelection`] of the atoms named `name`, such as `"CA"`.
+    pub fn select_atom_name(&self, name: &str) -> AtomSelection {
+        self.select_where(|atom_name, _, _| atom_name == name)
+    }
+
+    /// Build an [`AtomSelection`] of the atoms in residues named `name`, such as `"SOL"`.
+    pub fn select_residue_name(&self, name: &str) -> AtomSelection {
+        self.select_where(|_, residue_name, _| residue_name == name)
+    }
+
+    /// Build an [`AtomSelection`] of the atoms whose residue id lies in `start..=end`.
+    ///
+    /// The bounds follow the 1-based residue numbering of the `.gro` file, and both are
+    /// inclusive.
+    pub fn select_residue_range(&self, start: u32, end: u32) -> AtomSelection {
+        self.select_where(|_, _, residue_id| (start..=end).contains(&residue_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRO: &str = "\
+Alanine dipeptide in a box
+    8
+    1ALA      N    1   0.100   0.200   0.300
+    1ALA     CA    2   0.200   0.300   0.400
+    1ALA      C    3   0.300   0.400   0.500
+    2GLY      N    4   0.400   0.500   0.600
+    2GLY     CA    5   0.500   0.600   0.700
+    2GLY      C    6   0.600   0.700   0.800
+    3SOL     OW    7   0.700   0.800   0.900
+    3SOL    HW1    8   0.800   0.900   1.000
+   2.00000   2.00000   2.00000
+";
+
+    #[test]
+    fn parse_and_select() -> io::Result<()> {
+        let topology = Topology::from_gro(GRO.as_bytes())?;
+        assert_eq!(topology.natoms(), 8);
+        assert_eq!(topology.atom_name(1), Some("CA"));
+        assert_eq!(topology.residue_name(6), Some("SOL"));
+        assert_eq!(topology.residue_id(3), Some(2));
+        assert_eq!(topology.atom_name(8), None);
+
+        let n = topology.natoms();
+        let cas = topology.select_atom_name("CA");
+        assert_eq!(cas.to_index_list(n), [1, 4]);
+
+        let sol = topology.select_residue_name("SOL");
+        assert_eq!(sol.to_index_list(n), [6, 7]);
+
+        let residues = topology.select_residue_range(2, 3);
+        assert_eq!(residues.to_index_list(n), [3, 4, 5, 6, 7]);
+
+        // The escape hatch composes conditions over all three fields.
+        let gly_backbone = topology.select_where(|atom, residue, _| {
+            residue == "GLY" && matches!(atom, "N" | "CA" | "C")
+        });
+        assert_eq!(gly_backbone.to_index_list(n), [3, 4, 5]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        // The file may not end before the declared number of atoms.
+        assert!(Topology::from_gro("title\n    3\n    1ALA      N    1\n".as_bytes()).is_err());
+        // Atom lines must be long enough to hold the fixed-width fields.
+        assert!(Topology::from_gro("title\n    1\n    1ALA\n".as_bytes()).is_err());
+        // The natoms line must hold a number.
+        assert!(Topology::from_gro("title\nmany\n".as_bytes()).is_err());
+    }
+}
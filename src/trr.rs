@@ -0,0 +1,506 @@
+//! A reader for the GROMACS trr trajectory format.
+//!
+//! Where xtc only stores compressed coordinates, trr carries plain uncompressed arrays and may
+//! include velocities and forces next to the positions. The format is a simple sequence of xdr
+//! frames: a header listing the sizes of the blocks that follow, then the blocks themselves (box,
+//! virial, pressure, positions, velocities, forces), each of which may be present or absent per
+//! frame.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use glam::Mat3;
+
+use crate::reader::{read_f32, read_i32, read_u32};
+use crate::selection::AtomSelection;
+use crate::stream::discard_bytes;
+use crate::BoxVec;
+
+/// The magic number at the start of every trr frame.
+const TRR_MAGIC: i32 = 1993;
+/// The version string that follows the magic number.
+const TRR_VERSION: &str = "GMX_trn_file";
+
+/// The header of a single trr frame.
+///
+/// The `*_size` fields record the byte sizes of the blocks that follow the header, where a size of
+/// zero means the block is absent from this frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TRRHeader {
+    pub ir_size: u32,
+    pub e_size: u32,
+    pub box_size: u32,
+    pub vir_size: u32,
+    pub pres_size: u32,
+    pub top_size: u32,
+    pub sym_size: u32,
+    pub x_size: u32,
+    pub v_size: u32,
+    pub f_size: u32,
+    pub natoms: usize,
+    pub step: u32,
+    pub nre: i32,
+    pub time: f32,
+    pub lambda: f32,
+    /// Whether the blocks of this frame store 64-bit doubles rather than 32-bit floats.
+    pub double: bool,
+}
+
+impl TRRHeader {
+    pub fn read(file: &mut impl Read) -> io::Result<Self> {
+        let magic = read_i32(file)?;
+        if magic != TRR_MAGIC {
+            return Err(io::Error::other(format!(
+                "found invalid trr magic number '{magic}' ({magic:#0x}), expected {TRR_MAGIC}"
+            )));
+        }
+
+        // The version string "GMX_trn_file", stored as its length including the terminating nul,
+        // its length without it, and then the unpadded bytes.
+        let slen = read_u32(file)?;
+        let len = read_u32(file)?;
+        if slen != TRR_VERSION.len() as u32 + 1 || len != TRR_VERSION.len() as u32 {
+            return Err(io::Error::other(format!(
+                "unexpected trr version string length ({slen}, {len})"
+            )));
+        }
+        let mut version = [0; TRR_VERSION.len()];
+        file.read_exact(&mut version)?;
+        if version != TRR_VERSION.as_bytes() {
+            return Err(io::Error::other(format!(
+                "unexpected trr version string {:?}",
+                String::from_utf8_lossy(&version)
+            )));
+        }
+
+        let ir_size = read_u32(file)?;
+        let e_size = read_u32(file)?;
+        let box_size = read_u32(file)?;
+        let vir_size = read_u32(file)?;
+        let pres_size = read_u32(file)?;
+        let top_size = read_u32(file)?;
+        let sym_size = read_u32(file)?;
+        let x_size = read_u32(file)?;
+        let v_size = read_u32(file)?;
+        let f_size = read_u32(file)?;
+        let natoms: usize = read_u32(file)?
+            .try_into()
+            .map_err(|err| io::Error::other(format!("could not read natoms: {err}")))?;
+        let step: u32 = read_i32(file)?
+            .try_into()
+            .map_err(|err| io::Error::other(format!("could not read step: {err}")))?;
+        let nre = read_i32(file)?;
+
+        // Whether the frame is double-precision follows from the stored block sizes.
+        let double = box_size == 9 * 8 || x_size == natoms as u32 * 3 * 8;
+        let time = read_real(file, double)?;
+        let lambda = read_real(file, double)?;
+
+        Ok(Self {
+            ir_size,
+            e_size,
+            box_size,
+            vir_size,
+            pres_size,
+            top_size,
+            sym_size,
+            x_size,
+            v_size,
+            f_size,
+            natoms,
+            step,
+            nre,
+            time,
+            lambda,
+            double,
+        })
+    }
+
+    /// Returns the total byte size of the blocks that follow this header.
+    fn blocks_size(&self) -> u64 {
+        [
+            self.box_size,
+            self.vir_size,
+            self.pres_size,
+            self.x_size,
+            self.v_size,
+            self.f_size,
+        ]
+        .iter()
+        .map(|&size| size as u64)
+        .sum()
+    }
+}
+
+/// A single frame of a trr trajectory.
+///
+/// The velocity and force blocks are optional in the format, and [`None`] here reflects their
+/// absence from the frame. Double-precision trajectories are converted to `f32` on reading.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TRRFrame {
+    pub step: u32,
+    /// Time in picoseconds.
+    pub time: f32,
+    /// The coupling parameter of free energy calculations.
+    pub lambda: f32,
+    pub boxvec: BoxVec,
+    pub positions: Vec<f32>,
+    pub velocities: Option<Vec<f32>>,
+    pub forces: Option<Vec<f32>>,
+}
+
+impl TRRFrame {
+    /// Returns the number of atoms in this [`TRRFrame`].
+    pub fn natoms(&self) -> usize {
+        let npos = self.positions.len();
+        assert_eq!(
+            npos % 3,
+            0,
+            "the number of single positions in a frame must always be a multiple of 3"
+        );
+        npos / 3
+    }
+}
+
+/// A reader for trr trajectories, paralleling [`XTCReader`](crate::XTCReader).
+#[derive(Debug, Clone)]
+pub struct TRRReader<R> {
+    pub file: R,
+    pub step: usize,
+}
+
+impl TRRReader<File> {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        Ok(Self::new(file))
+    }
+}
+
+impl<R: Read> TRRReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            file: reader,
+            step: 0,
+        }
+    }
+
+    /// Read the header at the start of a frame.
+    ///
+    /// Assumes the internal reader is at the start of a new frame header.
+    pub fn read_header(&mut self) -> io::Result<TRRHeader> {
+        TRRHeader::read(&mut self.file)
+    }
+
+    /// Reads and returns a [`TRRFrame`] and advances one step.
+    pub fn read_frame(&mut self, frame: &mut TRRFrame) -> io::Result<()> {
+        self.read_frame_with_selection(frame, &AtomSelection::All)
+    }
+
+    /// Read the next frame into `frame`, reusing its allocations.
+    ///
+    /// Returns `false` once the end of the trajectory is reached.
+    pub fn read_frame_into(&mut self, frame: &mut TRRFrame) -> io::Result<bool> {
+        match self.read_frame(frame) {
+            Ok(()) => Ok(true),
+            // We have found the end of the file. No more frames, we're done.
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Reads and returns a [`TRRFrame`] according to the [`AtomSelection`], and advances one step.
+    ///
+    /// The selection applies to the position, velocity, and force blocks alike, such that the
+    /// arrays of the returned frame stay in register with each other.
+    pub fn read_frame_with_selection(
+        &mut self,
+        frame: &mut TRRFrame,
+        atom_selection: &AtomSelection,
+    ) -> io::Result<()> {
+        let header = self.read_header()?;
+        let file = &mut self.file;
+
+        // The virial and pressure blocks hold no place in a TRRFrame, and are discarded.
+        frame.boxvec = match header.box_size {
+            0 => Mat3::ZERO,
+            _ => read_matrix(file, header.double)?,
+        };
+        discard_bytes(file, (header.vir_size + header.pres_size) as u64)?;
+
+        frame.positions.clear();
+        if header.x_size != 0 {
+            read_atom_array(
+                file,
+                header.natoms,
+                header.double,
+                atom_selection,
+                &mut frame.positions,
+            )?;
+        }
+        frame.velocities = match header.v_size {
+            0 => None,
+            _ => {
+                let mut velocities = frame.velocities.take().unwrap_or_default();
+                velocities.clear();
+                read_atom_array(
+                    file,
+                    header.natoms,
+                    header.double,
+                    atom_selection,
+                    &mut velocities,
+                )?;
+                Some(velocities)
+            }
+        };
+        frame.forces = match header.f_size {
+            0 => None,
+            _ => {
+                let mut forces = frame.forces.take().unwrap_or_default();
+                forces.clear();
+                read_atom_array(file, header.natoms, header.double, atom_selection, &mut forces)?;
+                Some(forces)
+            }
+        };
+
+        self.step += 1;
+
+        frame.step = header.step;
+        frame.time = header.time;
+        frame.lambda = header.lambda;
+
+        Ok(())
+    }
+
+    /// Consume the next frame without decoding its blocks.
+    ///
+    /// Returns `false` once the end of the trajectory is reached. Together with a
+    /// [`FrameSelection`](crate::selection::FrameSelection), this allows cheaply striding through
+    /// a trajectory:
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use molly::selection::FrameSelection;
+    ///
+    /// let mut reader = molly::TRRReader::open("trajectory.trr")?;
+    /// let selection = FrameSelection::All.downsample(10.try_into().unwrap());
+    /// let mut frame = molly::trr::TRRFrame::default();
+    /// let mut idx = 0;
+    /// loop {
+    ///     let more = match selection.is_included(idx) {
+    ///         Some(true) => reader.read_frame_into(&mut frame)?,
+    ///         Some(false) => reader.skip_frame()?,
+    ///         None => break,
+    ///     };
+    ///     if !more {
+    ///         break;
+    ///     }
+    ///     idx += 1;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn skip_frame(&mut self) -> io::Result<bool> {
+        let header = match self.read_header() {
+            Ok(header) => header,
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+            Err(err) => return Err(err),
+        };
+        discard_bytes(&mut self.file, header.blocks_size())?;
+        self.step += 1;
+        Ok(true)
+    }
+}
+
+fn read_f64<R: Read>(file: &mut R) -> io::Result<f64> {
+    let mut buf = [0; 8];
+    file.read_exact(&mut buf)?;
+    Ok(f64::from_be_bytes(buf))
+}
+
+/// Read one real value of the frame's precision, converted to `f32`.
+fn read_real<R: Read>(file: &mut R, double: bool) -> io::Result<f32> {
+    if double {
+        Ok(read_f64(file)? as f32)
+    } else {
+        read_f32(file)
+    }
+}
+
+/// Read a 3×3 matrix block, such as the simulation box.
+fn read_matrix<R: Read>(file: &mut R, double: bool) -> io::Result<Mat3> {
+    let mut values = [0.0; 9];
+    for value in &mut values {
+        *value = read_real(file, double)?;
+    }
+    Ok(Mat3::from_cols_array(&values))
+}
+
+/// Read a per-atom array block (positions, velocities, or forces) according to an
+/// [`AtomSelection`], appending the selected values to `out`.
+///
+/// The whole block is always consumed, such that the reader ends up at the start of the next
+/// block regardless of the selection.
+fn read_atom_array<R: Read>(
+    file: &mut R,
+    natoms: usize,
+    double: bool,
+    atom_selection: &AtomSelection,
+    out: &mut Vec<f32>,
+) -> io::Result<()> {
+    let width = if double { 8 } else { 4 };
+    for idx in 0..natoms {
+        match atom_selection.is_included(idx) {
+            Some(true) => {
+                for _ in 0..3 {
+                    out.push(read_real(file, double)?);
+                }
+            }
+            Some(false) => discard_bytes(file, 3 * width)?,
+            // The selection holds no atoms beyond this point; consume the rest of the block.
+            None => {
+                discard_bytes(file, (natoms - idx) as u64 * 3 * width)?;
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serialize a [`TRRFrame`] into trr wire format, as a stand-in for GROMACS output.
+    fn write_frame(bytes: &mut Vec<u8>, frame: &TRRFrame) {
+        let natoms = frame.natoms() as u32;
+        bytes.extend(TRR_MAGIC.to_be_bytes());
+        bytes.extend((TRR_VERSION.len() as u32 + 1).to_be_bytes());
+        bytes.extend((TRR_VERSION.len() as u32).to_be_bytes());
+        bytes.extend(TRR_VERSION.as_bytes());
+        bytes.extend(0u32.to_be_bytes()); // ir_size
+        bytes.extend(0u32.to_be_bytes()); // e_size
+        bytes.extend((9u32 * 4).to_be_bytes()); // box_size
+        bytes.extend(0u32.to_be_bytes()); // vir_size
+        bytes.extend(0u32.to_be_bytes()); // pres_size
+        bytes.extend(0u32.to_be_bytes()); // top_size
+        bytes.extend(0u32.to_be_bytes()); // sym_size
+        let array_size = |array: Option<&Vec<f32>>| array.map_or(0, |a| a.len() as u32 * 4);
+        bytes.extend(array_size(Some(&frame.positions)).to_be_bytes());
+        bytes.extend(array_size(frame.velocities.as_ref()).to_be_bytes());
+        bytes.extend(array_size(frame.forces.as_ref()).to_be_bytes());
+        bytes.extend(natoms.to_be_bytes());
+        bytes.extend(frame.step.to_be_bytes());
+        bytes.extend(0u32.to_be_bytes()); // nre
+        bytes.extend(frame.time.to_be_bytes());
+        bytes.extend(frame.lambda.to_be_bytes());
+        for value in frame.boxvec.to_cols_array() {
+            bytes.extend(value.to_be_bytes());
+        }
+        for array in [
+            Some(&frame.positions),
+            frame.velocities.as_ref(),
+            frame.forces.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            for value in array {
+                bytes.extend(value.to_be_bytes());
+            }
+        }
+    }
+
+    fn fixture() -> (Vec<TRRFrame>, Vec<u8>) {
+        let natoms = 5;
+        let array = |offset: f32| (0..natoms * 3).map(|v| v as f32 * 0.1 + offset).collect();
+        let frames = vec![
+            // A frame with all three arrays present.
+            TRRFrame {
+                step: 0,
+                time: 0.0,
+                lambda: 0.5,
+                boxvec: Mat3::from_diagonal([10.0, 10.0, 10.0].into()),
+                positions: array(0.0),
+                velocities: Some(array(100.0)),
+                forces: Some(array(200.0)),
+            },
+            // A frame with positions only.
+            TRRFrame {
+                step: 100,
+                time: 2.0,
+                lambda: 0.5,
+                boxvec: Mat3::from_diagonal([10.0, 10.0, 10.0].into()),
+                positions: array(1.0),
+                velocities: None,
+                forces: None,
+            },
+        ];
+        let mut bytes = Vec::new();
+        for frame in &frames {
+            write_frame(&mut bytes, frame);
+        }
+        (frames, bytes)
+    }
+
+    #[test]
+    fn read_fixture() -> io::Result<()> {
+        let (expected, bytes) = fixture();
+        let mut reader = TRRReader::new(bytes.as_slice());
+
+        let mut frame = TRRFrame::default();
+        for expected in &expected {
+            assert!(reader.read_frame_into(&mut frame)?);
+            assert_eq!(&frame, expected);
+        }
+        assert!(!reader.read_frame_into(&mut frame)?);
+        assert_eq!(reader.step, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn atom_selection_stays_in_register() -> io::Result<()> {
+        let (expected, bytes) = fixture();
+        let mut reader = TRRReader::new(bytes.as_slice());
+
+        // Select atoms 1 and 3 from every array.
+        let selection = AtomSelection::from_index_list(&[1, 3]);
+        let mut frame = TRRFrame::default();
+        reader.read_frame_with_selection(&mut frame, &selection)?;
+
+        let select = |array: &[f32]| {
+            [&array[3..6], &array[9..12]].concat()
+        };
+        assert_eq!(frame.positions, select(&expected[0].positions));
+        assert_eq!(
+            frame.velocities.as_deref(),
+            Some(select(expected[0].velocities.as_ref().unwrap()).as_slice())
+        );
+        assert_eq!(
+            frame.forces.as_deref(),
+            Some(select(expected[0].forces.as_ref().unwrap()).as_slice())
+        );
+
+        // The whole block is consumed regardless of the selection, so the next frame reads fine.
+        reader.read_frame_with_selection(&mut frame, &selection)?;
+        assert_eq!(frame.step, 100);
+        assert_eq!(frame.velocities, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn skip_frame_lands_on_next_header() -> io::Result<()> {
+        let (_, bytes) = fixture();
+        let mut reader = TRRReader::new(bytes.as_slice());
+
+        assert!(reader.skip_frame()?);
+        let mut frame = TRRFrame::default();
+        reader.read_frame(&mut frame)?;
+        assert_eq!(frame.step, 100);
+        assert!(!reader.skip_frame()?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,459 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::reader::{calc_sizeint, FIRSTIDX, MAGICINTS, NBYTES_POSITIONS_PRELUDE};
+use crate::{padding, Frame, Header, Magic, XTC_1995_MAX_NATOMS};
+
+/// The default coordinate precision, in case a [`Frame`] does not specify a valid one.
+const DEFAULT_PRECISION: f32 = 1000.0;
+
+/// The state for the low-level bit-packing routines.
+///
+/// This is the encoding counterpart to the `DecodeState` in the [`reader`](crate::reader) module.
+struct EncodeState {
+    bytes: Vec<u8>,
+    lastbits: usize,
+    lastbyte: u64,
+}
+
+impl EncodeState {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            lastbits: 0,
+            lastbyte: 0,
+        }
+    }
+
+    /// Flush any remaining bits and return the encoded bytes.
+    fn finish(mut self) -> Vec<u8> {
+        if self.lastbits > 0 {
+            self.bytes
+                .push((self.lastbyte << (8 - self.lastbits)) as u8);
+        }
+        self.bytes
+    }
+}
+
+fn sendbits(state: &mut EncodeState, mut nbits: usize, num: u32) {
+    let num = num as u64;
+    let mut lastbits = state.lastbits;
+    let mut lastbyte = state.lastbyte;
+
+    while nbits >= 8 {
+        lastbyte = (lastbyte << 8) | ((num >> (nbits - 8)) & 0xff);
+        state.bytes.push((lastbyte >> lastbits) as u8);
+        nbits -= 8;
+    }
+
+    if nbits > 0 {
+        lastbyte = (lastbyte << nbits) | (num & ((1 << nbits) - 1));
+        lastbits += nbits;
+        if lastbits >= 8 {
+            lastbits -= 8;
+            state.bytes.push((lastbyte >> lastbits) as u8);
+        }
+    }
+
+    state.lastbits = lastbits;
+    state.lastbyte = lastbyte & 0xff;
+}
+
+fn sendints(state: &mut EncodeState, nbits: u32, sizes: [u32; 3], nums: [u32; 3]) {
+    let mut bytes = [0u8; 32];
+    let mut nbytes: usize = 0;
+
+    let mut tmp = nums[0] as u64;
+    loop {
+        bytes[nbytes] = (tmp & 0xff) as u8;
+        nbytes += 1;
+        tmp >>= 8;
+        if tmp == 0 {
+            break;
+        }
+    }
+
+    for i in 1..3 {
+        debug_assert!(
+            nums[i] < sizes[i],
+            "major breakdown in sendints: num {} >= size {}",
+            nums[i],
+            sizes[i]
+        );
+        // Use one-step multiplication, adding the next number into the little-endian byte string.
+        let mut tmp = nums[i] as u64;
+        let mut bytecount = 0;
+        while bytecount < nbytes {
+            tmp += bytes[bytecount] as u64 * sizes[i] as u64;
+            bytes[bytecount] = (tmp & 0xff) as u8;
+            tmp >>= 8;
+            bytecount += 1;
+        }
+        while tmp != 0 {
+            bytes[bytecount] = (tmp & 0xff) as u8;
+            bytecount += 1;
+            tmp >>= 8;
+        }
+        nbytes = bytecount;
+    }
+
+    let nbits = nbits as usize;
+    if nbits >= nbytes * 8 {
+        for &byte in &bytes[..nbytes] {
+            sendbits(state, 8, byte as u32);
+        }
+        sendbits(state, nbits - nbytes * 8, 0);
+    } else {
+        for &byte in &bytes[..nbytes - 1] {
+            sendbits(state, 8, byte as u32);
+        }
+        sendbits(state, nbits - (nbytes - 1) * 8, bytes[nbytes - 1] as u32);
+    }
+}
+
+/// The low-level compression routine, the counterpart to
+/// [`read_compressed_positions`](crate::reader::read_compressed_positions).
+///
+/// Returns the encoded bytes for the positions block, starting at the `minint` values. The
+/// `precision` header field and the number of encoded bytes are written by the caller.
+fn compress_positions(ints: &mut [i32], precision_bytes: &mut Vec<u8>) {
+    let natoms = {
+        let n = ints.len();
+        assert_eq!(n % 3, 0, "the length of `ints` must be divisible by 3");
+        n / 3
+    };
+
+    let mut minint = [i32::MAX; 3];
+    let mut maxint = [i32::MIN; 3];
+    let mut mindiff = i32::MAX;
+    let mut oldlint = [0i32; 3];
+    for (idx, lint) in ints.chunks_exact(3).enumerate() {
+        for d in 0..3 {
+            minint[d] = minint[d].min(lint[d]);
+            maxint[d] = maxint[d].max(lint[d]);
+        }
+        let diff: i32 = (0..3).map(|d| (oldlint[d] - lint[d]).abs()).sum();
+        if diff < mindiff && idx >= 1 {
+            mindiff = diff;
+        }
+        oldlint = [lint[0], lint[1], lint[2]];
+    }
+
+    for min in minint {
+        precision_bytes.extend(min.to_be_bytes());
+    }
+    for max in maxint {
+        precision_bytes.extend(max.to_be_bytes());
+    }
+
+    let mut sizeint = [0u32; 3];
+    let mut bitsizeint = [0u32; 3];
+    let bitsize = calc_sizeint(minint, maxint, &mut sizeint, &mut bitsizeint);
+
+    let lastidx = MAGICINTS.len() - 1;
+    let mut smallidx = FIRSTIDX;
+    while smallidx < lastidx && MAGICINTS[smallidx] < mindiff {
+        smallidx += 1;
+    }
+    precision_bytes.extend((smallidx as u32).to_be_bytes());
+
+    let maxidx = usize::min(lastidx, smallidx + 8);
+    let minidx = maxidx - 8; // Often this equals smallidx.
+    let mut smaller = MAGICINTS[usize::max(FIRSTIDX, smallidx - 1)] / 2;
+    let mut smallnum = MAGICINTS[smallidx] / 2;
+    let mut sizesmall = [MAGICINTS[smallidx] as u32; 3];
+    let larger = MAGICINTS[maxidx] / 2;
+
+    let mut state = EncodeState::new();
+    let mut prevcoord = [0i32; 3];
+    let mut prevrun: i32 = -1;
+    let mut tmpcoord = [0u32; 8 * 3 + 3];
+    let mut i = 0;
+    while i < natoms {
+        let mut is_small = false;
+        let this = i * 3;
+        let mut is_smaller: i32 = if smallidx < maxidx
+            && i >= 1
+            && (ints[this] - prevcoord[0]).abs() < larger
+            && (ints[this + 1] - prevcoord[1]).abs() < larger
+            && (ints[this + 2] - prevcoord[2]).abs() < larger
+        {
+            1
+        } else if smallidx > minidx {
+            -1
+        } else {
+            0
+        };
+        if i + 1 < natoms
+            && (ints[this] - ints[this + 3]).abs() < smallnum
+            && (ints[this + 1] - ints[this + 4]).abs() < smallnum
+            && (ints[this + 2] - ints[this + 5]).abs() < smallnum
+        {
+            // Interchange the first and second atom, for better compression of water molecules.
+            // (This is undone by the corresponding swap in the decompression routine.)
+            ints.swap(this, this + 3);
+            ints.swap(this + 1, this + 4);
+            ints.swap(this + 2, this + 5);
+            is_small = true;
+        }
+
+        tmpcoord[0] = (ints[this] - minint[0]) as u32;
+        tmpcoord[1] = (ints[this + 1] - minint[1]) as u32;
+        tmpcoord[2] = (ints[this + 2] - minint[2]) as u32;
+        if bitsize == 0 {
+            sendbits(&mut state, bitsizeint[0] as usize, tmpcoord[0]);
+            sendbits(&mut state, bitsizeint[1] as usize, tmpcoord[1]);
+            sendbits(&mut state, bitsizeint[2] as usize, tmpcoord[2]);
+        } else {
+            sendints(
+                &mut state,
+                bitsize,
+                sizeint,
+                [tmpcoord[0], tmpcoord[1], tmpcoord[2]],
+            );
+        }
+        prevcoord = [ints[this], ints[this + 1], ints[this + 2]];
+        i += 1;
+
+        let mut run: i32 = 0;
+        if !is_small && is_smaller == -1 {
+            is_smaller = 0;
+        }
+        while is_small && run < 8 * 3 {
+            let this = i * 3;
+            if is_smaller == -1 {
+                let dist = (0..3)
+                    .map(|d| {
+                        let diff = (ints[this + d] - prevcoord[d]) as i64;
+                        diff * diff
+                    })
+                    .sum::<i64>();
+                if dist >= smaller as i64 * smaller as i64 {
+                    is_smaller = 0;
+                }
+            }
+
+            for d in 0..3 {
+                tmpcoord[run as usize + d] = (ints[this + d] - prevcoord[d] + smallnum) as u32;
+            }
+            run += 3;
+            prevcoord = [ints[this], ints[this + 1], ints[this + 2]];
+
+            i += 1;
+            is_small = i < natoms
+                && (ints[i * 3] - prevcoord[0]).abs() < smallnum
+                && (ints[i * 3 + 1] - prevcoord[1]).abs() < smallnum
+                && (ints[i * 3 + 2] - prevcoord[2]).abs() < smallnum;
+        }
+
+        if run != prevrun || is_smaller != 0 {
+            prevrun = run;
+            sendbits(&mut state, 1, 1); // Flag the change in run length.
+            sendbits(&mut state, 5, (run + is_smaller + 1) as u32);
+        } else {
+            sendbits(&mut state, 1, 0); // Flag the fact that the run length did not change.
+        }
+        for k in (0..run as usize).step_by(3) {
+            sendints(
+                &mut state,
+                smallidx as u32,
+                sizesmall,
+                [tmpcoord[k], tmpcoord[k + 1], tmpcoord[k + 2]],
+            );
+        }
+        if is_smaller != 0 {
+            if is_smaller < 0 {
+                smallidx -= 1;
+                smallnum = smaller;
+                if smallidx > FIRSTIDX {
+                    smaller = MAGICINTS[smallidx - 1] / 2;
+                } else {
+                    smaller = 0;
+                }
+            } else {
+                smallidx += 1;
+                smaller = smallnum;
+                smallnum = MAGICINTS[smallidx] / 2;
+            }
+            sizesmall.fill(MAGICINTS[smallidx] as u32);
+        }
+    }
+
+    precision_bytes.extend(state.finish());
+}
+
+/// A writer for xtc trajectories, the counterpart to [`XTCReader`](crate::XTCReader).
+///
+/// Frames are compressed with the same scheme [`XTCReader`](crate::XTCReader) decodes, such that a
+/// frame that is written out and read back in yields the original positions within the encoded
+/// precision.
+#[derive(Debug, Clone)]
+pub struct XTCWriter<W> {
+    pub file: W,
+    pub step: usize,
+}
+
+impl XTCWriter<File> {
+    /// Create a file at the provided path and return a writer for it.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self::new(file))
+    }
+}
+
+impl<W: Write> XTCWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            file: writer,
+            step: 0,
+        }
+    }
+
+    /// Write a [`Frame`] and advance one step.
+    ///
+    /// The magic number is chosen based on the number of atoms in the frame: frames that exceed
+    /// [`XTC_1995_MAX_NATOMS`] are written with the 2023 magic number, all others with the 1995
+    /// magic number that any xtc reader understands.
+    ///
+    /// If the `precision` of the frame is zero or negative, a default precision of 1000 is used.
+    pub fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        let natoms = frame.natoms();
+        let magic = if natoms > XTC_1995_MAX_NATOMS {
+            Magic::Xtc2023
+        } else {
+            Magic::Xtc1995
+        };
+        let header = Header {
+            magic,
+            natoms,
+            step: frame.step,
+            time: frame.time,
+            boxvec: frame.boxvec,
+            natoms_repeated: natoms,
+        };
+        self.file.write_all(&header.to_be_bytes())?;
+
+        if natoms <= 9 {
+            // In case the number of atoms is very small, the positions are stored uncompressed,
+            // and no precision field is written.
+            for pos in &frame.positions {
+                self.file.write_all(&pos.to_be_bytes())?;
+            }
+            self.step += 1;
+            return Ok(());
+        }
+
+        let precision = if frame.precision > 0.0 {
+            frame.precision
+        } else {
+            DEFAULT_PRECISION
+        };
+        self.file.write_all(&precision.to_be_bytes())?;
+
+        // Round the positions to integers according to the precision.
+        let mut ints = Vec::with_capacity(frame.positions.len());
+        for &pos in &frame.positions {
+            let lf = pos * precision;
+            let lf = if lf >= 0.0 { lf + 0.5 } else { lf - 0.5 };
+            if lf.abs() > (i32::MAX - 2) as f32 {
+                return Err(io::Error::other(format!(
+                    "cannot compress position {pos}: the scaled value exceeds the integer range"
+                )));
+            }
+            ints.push(lf as i32);
+        }
+
+        let mut encoded = Vec::new();
+        compress_positions(&mut ints, &mut encoded);
+
+        // The prelude (minint, maxint, smallidx) is part of `encoded`, but the byte count stored
+        // in the frame only covers the compressed stream that follows it.
+        let nbytes = encoded.len() - NBYTES_POSITIONS_PRELUDE;
+        let (prelude, compressed) = encoded.split_at(NBYTES_POSITIONS_PRELUDE);
+        self.file.write_all(prelude)?;
+        match magic {
+            Magic::Xtc1995 => self.file.write_all(&(nbytes as u32).to_be_bytes())?,
+            Magic::Xtc2023 => self.file.write_all(&(nbytes as u64).to_be_bytes())?,
+        }
+        self.file.write_all(compressed)?;
+        // Note that we are dealing with xdr padding, here! (32-bit blocks.)
+        self.file.write_all(&vec![0; padding(nbytes)])?;
+
+        self.step += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::XTCReader;
+
+    /// A deterministic pseudo-random walk, vaguely resembling real coordinates.
+    fn walk_positions(natoms: usize) -> Vec<f32> {
+        let mut positions = Vec::with_capacity(natoms * 3);
+        let mut x: u32 = 0xdeadbeef;
+        let mut pos = [1.0f32; 3];
+        for _ in 0..natoms {
+            for p in &mut pos {
+                // An xorshift step to produce the displacement.
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                *p += (x as f32 / u32::MAX as f32 - 0.5) * 0.02;
+                positions.push(*p);
+            }
+        }
+        positions
+    }
+
+    fn roundtrip(natoms: usize) -> io::Result<()> {
+        let precision = 1000.0;
+        let frame = Frame {
+            step: 7,
+            time: 0.42,
+            boxvec: crate::BoxVec::from_diagonal(glam::Vec3::splat(4.2)),
+            precision,
+            positions: walk_positions(natoms),
+        };
+
+        let mut writer = XTCWriter::new(Cursor::new(Vec::new()));
+        writer.write_frame(&frame)?;
+
+        let mut reader = XTCReader::new(Cursor::new(writer.file.into_inner()));
+        let mut read_back = Frame::default();
+        reader.read_frame(&mut read_back)?;
+
+        assert_eq!(read_back.step, frame.step);
+        assert_eq!(read_back.time, frame.time);
+        assert_eq!(read_back.boxvec, frame.boxvec);
+        assert_eq!(read_back.natoms(), natoms);
+        for (read, original) in read_back.positions.iter().zip(&frame.positions) {
+            assert!(
+                (read - original).abs() <= 0.5 / precision + f32::EPSILON,
+                "position {read} deviates from {original} beyond the encoded precision"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrip_smol() -> io::Result<()> {
+        // Up to 9 atoms, the uncompressed layout is used.
+        roundtrip(5)
+    }
+
+    #[test]
+    fn roundtrip_compressed() -> io::Result<()> {
+        roundtrip(125)
+    }
+
+    #[test]
+    fn roundtrip_compressed_large() -> io::Result<()> {
+        roundtrip(10_000)
+    }
+}
@@ -137,3 +137,69 @@ fn compare_bad() -> std::io::Result<()> {
 fn compare_delinyah() -> std::io::Result<()> {
     compare(trajectories::DELINYAH)
 }
+
+/// Anchor the writer against an external implementation: frames written by molly must read back
+/// identically through xdrfile, for both the uncompressed small-system path and the compressed
+/// path.
+#[test]
+fn compare_written_output() -> std::io::Result<()> {
+    for (name, natoms) in [("smol", 5usize), ("compressed", 60)] {
+        let path = std::env::temp_dir().join(format!(
+            "molly_write_compare_{name}_{}.xtc",
+            std::process::id()
+        ));
+
+        let mut writer = molly::XTCWriter::create(&path)?;
+        let mut written = Vec::new();
+        for step in 0..4u32 {
+            let frame = molly::Frame {
+                step,
+                time: step as f32 * 0.5,
+                boxvec: glam::Mat3::from_diagonal(glam::Vec3::new(2.0, 3.0, 4.0)),
+                precision: 1000.0,
+                positions: (0..3 * natoms)
+                    .map(|v| (v + step as usize * 100) as f32 * 0.01)
+                    .collect(),
+            };
+            writer.write_frame(&frame)?;
+            written.push(frame);
+        }
+
+        let mut xdr_reader =
+            xdrfile::XTCTrajectory::open_read(&path).expect("couldn't open file using xdrfile");
+        assert_eq!(
+            xdr_reader
+                .get_num_atoms()
+                .expect("couldn't get number of atoms from xdrfile"),
+            natoms
+        );
+        let mut xdr_frame = xdrfile::Frame::with_len(natoms);
+        for frame in &written {
+            xdr_reader
+                .read(&mut xdr_frame)
+                .expect("couldn't read xdrfile frame");
+            assert_eq!(xdr_frame.step, frame.step as usize);
+            assert_eq!(xdr_frame.time, frame.time);
+            // The box vectors are stored as the columns of `boxvec`.
+            assert_eq!(xdr_frame.box_vector, frame.boxvec.to_cols_array_2d());
+            for (i, xdr_pos) in xdr_frame.coords.iter().enumerate() {
+                for dim in 0..3 {
+                    let expected = frame.positions[i * 3 + dim];
+                    // The compressed path rounds to the frame precision; the small-system path
+                    // stores the floats verbatim.
+                    assert!(
+                        (xdr_pos[dim] - expected).abs() <= 0.5 / frame.precision,
+                        "position {i} for molly-written output and xdrfile does not match"
+                    );
+                }
+            }
+        }
+        assert!(
+            xdr_reader.read(&mut xdr_frame).is_err(),
+            "xdrfile reader should be done by now"
+        );
+
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
@@ -250,22 +250,22 @@ mod atom_selection {
     /// Read according to a mask.
     #[test]
     fn mask() -> std::io::Result<()> {
-        assert_atoms!(AS::Mask(vec![true, false, false, true, false, true]) => 3)
+        assert_atoms!(AS::Mask(vec![true, false, false, true, false, true].into()) => 3)
     }
     /// Read according to an empty mask.
     #[test]
     fn mask_empty_list() -> std::io::Result<()> {
-        assert_atoms!(AS::Mask(vec![]) => 0)
+        assert_atoms!(AS::Mask(vec![].into()) => 0)
     }
     /// Read the first atom.
     #[test]
     fn mask_first_atom() -> std::io::Result<()> {
-        assert_atoms!(AS::Mask(vec![true]) => 1)
+        assert_atoms!(AS::Mask(vec![true].into()) => 1)
     }
     /// Read a single atom at some index.
     #[test]
     fn mask_single_atom() -> std::io::Result<()> {
-        assert_atoms!(AS::Mask([vec![false; 100], vec![true]].concat()) => 1)
+        assert_atoms!(AS::Mask([vec![false; 100], vec![true]].concat().into()) => 1)
     }
     /// Read only the last index.
     #[test]
@@ -273,7 +273,7 @@ mod atom_selection {
         let n = NATOMS;
         let mut mask = vec![false; n];
         mask[n - 1] = true;
-        assert_atoms!(AS::Mask(mask) => 1)
+        assert_atoms!(AS::Mask(mask.into()) => 1)
     }
     /// Read just beyond the last index.
     #[test]
@@ -281,7 +281,7 @@ mod atom_selection {
         let n = NATOMS + 1;
         let mut mask = vec![false; n];
         mask[n - 1] = true;
-        assert_atoms!(AS::Mask(mask) => 0)
+        assert_atoms!(AS::Mask(mask.into()) => 0)
     }
     /// Read far beyond the last index.
     #[test]
@@ -289,7 +289,7 @@ mod atom_selection {
         let n = NATOMS + 1000;
         let mut mask = vec![false; n];
         mask[n - 1] = true;
-        assert_atoms!(AS::Mask(mask) => 0)
+        assert_atoms!(AS::Mask(mask.into()) => 0)
     }
     /// Read according to a list of mask with some beyond the last atom.
     #[test]
@@ -301,7 +301,7 @@ mod atom_selection {
         mask[500] = true;
         mask[n - 500] = true;
         mask[n - 1] = true;
-        assert_atoms!(AS::Mask(mask) => 3)
+        assert_atoms!(AS::Mask(mask.into()) => 3)
     }
 
     #[test]